@@ -0,0 +1,344 @@
+use crate::players::*;
+use crate::tiles::*;
+
+use petgraph::{
+    dot::Dot,
+    stable_graph::{NodeIndex, StableGraph},
+    visit::Bfs,
+    Undirected,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TileMatrixCoords {
+    pub x: i32,
+    pub y: i32,
+}
+
+// Starts from north and goes clockwise.
+pub const NEIGHBOR_COORDS: [TileMatrixCoords; 4] = [
+    TileMatrixCoords { x: 0, y: 1 },
+    TileMatrixCoords { x: 1, y: 0 },
+    TileMatrixCoords { x: 0, y: -1 },
+    TileMatrixCoords { x: -1, y: 0 },
+];
+
+// Starts from north and goes clockwise, diagonals included.
+pub const SURROUNDING_COORDS: [TileMatrixCoords; 8] = [
+    TileMatrixCoords { x: 0, y: 1 },
+    TileMatrixCoords { x: 1, y: 1 },
+    TileMatrixCoords { x: 1, y: 0 },
+    TileMatrixCoords { x: 1, y: -1 },
+    TileMatrixCoords { x: 0, y: -1 },
+    TileMatrixCoords { x: -1, y: -1 },
+    TileMatrixCoords { x: -1, y: 0 },
+    TileMatrixCoords { x: -1, y: 1 },
+];
+
+pub fn get_neighbor_coords(coords: TileMatrixCoords, side: TileSide) -> TileMatrixCoords {
+    let offset = NEIGHBOR_COORDS[side as usize];
+    return TileMatrixCoords {
+        x: coords.x + offset.x,
+        y: coords.y + offset.y,
+    };
+}
+
+// #[derive(Clone, Debug, Eq, PartialEq)]
+// pub struct TileMatrixInfo {
+//     pub tile_index: TileIndex,
+// }
+
+// Hills & Sheep: what a shepherd can draw from the bag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SheepToken {
+    // One to four sheep.
+    Sheep(usize),
+    Wolf,
+}
+
+pub fn create_sheep_bag() -> Vec<SheepToken> {
+    let mut sheep_bag: Vec<SheepToken> = vec![];
+    for (token, qty) in [
+        (SheepToken::Sheep(1), 4),
+        (SheepToken::Sheep(2), 5),
+        (SheepToken::Sheep(3), 5),
+        (SheepToken::Sheep(4), 2),
+        (SheepToken::Wolf, 2),
+    ] {
+        sheep_bag.extend(std::iter::repeat(token).take(qty));
+    }
+    return sheep_bag;
+}
+
+// Bridges, Castles & Bazaars: a finished two tile town turned into a castle. Its meeple sits
+// on it until a feature in its fief, the tiles around it, is completed.
+#[derive(Clone, Debug)]
+pub struct Castle {
+    pub tiles: Vec<TileIndex>,
+    pub area: TileAreaIndex,
+    pub meeple: PlacedMeeple,
+}
+
+// A market tile being bid for. The auctioneer opens at nothing and every other player still
+// without a tile gets one chance to bid higher.
+#[derive(Clone, Debug)]
+pub struct Auction {
+    pub tile: TileIndex,
+    pub auctioneer: PlayerIndex,
+    pub high_bid: i32,
+    pub high_bidder: PlayerIndex,
+    // Players still to bid, in order.
+    pub bidders: Vec<PlayerIndex>,
+}
+
+// Areas connected across tiles into a single road, town, etc.
+#[derive(Clone, Debug)]
+pub struct Feature {
+    pub feature_type: FeatureType,
+    pub tiles: Vec<TileIndex>,
+    pub areas: Vec<TileAreaIndex>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameplayData {
+    // Seed for every random draw in the game, so the same seed replays the same game.
+    pub seed: u64,
+    pub rng: ChaCha8Rng,
+    pub spawned_tiles: Vec<TileIndex>,
+    pub unspawned_tiles: Vec<TileIndex>,
+    // River tiles still to play. They all go down before anything in unspawned_tiles.
+    pub river_tiles: Vec<TileIndex>,
+    // Last river tile and the side the river leaves it by, while the river is still open.
+    pub river_end: Option<(TileMatrixCoords, TileSide)>,
+    // Way the river bent last. Bending the same way twice would turn it back on itself.
+    pub last_river_turn: Option<RiverTurn>,
+    // Tiles drawn that had nowhere to go.
+    pub discarded_tiles: Vec<TileIndex>,
+    // pub board_tile_graph: StableGraph<TileIndex, TileDirection, Undirected>,
+    pub board_tile_matrix: HashMap<TileMatrixCoords, TileIndex>,
+    pub board_tile_matrix_inverse: HashMap<TileIndex, TileMatrixCoords>,
+    // Rotation each tile had when it was placed on the board.
+    pub board_tile_directions: HashMap<TileIndex, TileDirection>,
+    // pub tile_index_to_tile_graph_index: HashMap<TileIndex, NodeIndex>,
+    pub next_placeholder_index: TileIndex,
+    pub board_area_graph: StableGraph<TileAreaIndex, (), Undirected>,
+    pub area_index_to_area_graph_index: HashMap<TileAreaIndex, NodeIndex>,
+    pub area_meeples: HashMap<TileAreaIndex, PlacedMeeple>,
+    // Tile placed this turn, the only one meeples can go on and the one scoring starts from.
+    pub last_placed_tile: Option<TileIndex>,
+    // Traders & Builders: the current player gets to play again, or already is.
+    pub extra_turn_earned: bool,
+    pub in_extra_turn: bool,
+    // Princess & Dragon: where the dragon is, once a volcano has been placed.
+    pub dragon: Option<TileMatrixCoords>,
+    // Tiles the dragon went over this move. It can't go back over them.
+    pub dragon_path: Vec<TileMatrixCoords>,
+    pub dragon_moves_left: usize,
+    // Dragon tiles drawn before the dragon was out. They go back in the bag with the first
+    // volcano.
+    pub set_aside_tiles: Vec<TileIndex>,
+    // Area of the meeple the fairy stands next to.
+    pub fairy: Option<TileAreaIndex>,
+    // Abbey & Mayor: abbey tiles nobody has played yet.
+    pub abbey_tiles: Vec<TileIndex>,
+    // Wagons whose feature was just completed, with the area they stood on. They may move on
+    // before the next turn.
+    pub pending_wagons: Vec<(PlacedMeeple, TileAreaIndex)>,
+    // Tower: how many pieces are stacked on each tower foundation built on so far.
+    pub towers: HashMap<TileMatrixCoords, usize>,
+    // Tower built on this turn instead of placing a meeple. It gets to capture one.
+    pub tower_built: Option<TileMatrixCoords>,
+    // Hills & Sheep: tokens still in the bag, and the sheep drawn by the shepherd on each area.
+    pub sheep_bag: Vec<SheepToken>,
+    pub flocks: HashMap<TileAreaIndex, Vec<SheepToken>>,
+    // Bridges, Castles & Bazaars. The current player wants to lay a bridge across the tile
+    // they are placing.
+    pub bridge_requested: bool,
+    pub castles: Vec<Castle>,
+    // Towns completed this turn that the player may still turn into castles.
+    pub pending_castles: Vec<Feature>,
+    // A bazaar tile was placed this turn. The bazaar opens before the next player's turn.
+    pub bazaar_pending: bool,
+    // The tile being played was bought at a bazaar, so it won't open another one.
+    pub playing_bought_tile: bool,
+    // Tiles up for auction, players who still need one, and the auction going on.
+    pub market: Vec<TileIndex>,
+    pub bazaar_players: Vec<PlayerIndex>,
+    pub auction: Option<Auction>,
+}
+
+impl GameplayData {
+    // Empty board with every tile still in the bag, river tiles in a stack of their own and
+    // abbeys kept out of it.
+    pub fn new(tile_data: &GameTileData, seed: u64) -> GameplayData {
+        let (abbey_tiles, bag_tiles): (Vec<TileIndex>, Vec<TileIndex>) =
+            (0..tile_data.all_tiles.len())
+                .partition(|t| tile_type_is_abbey(tile_data.all_tiles[*t].tile_type));
+        let (river_tiles, unspawned_tiles): (Vec<TileIndex>, Vec<TileIndex>) = bag_tiles
+            .into_iter()
+            .partition(|t| tile_type_is_river(tile_data.all_tiles[*t].tile_type));
+        return GameplayData {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            spawned_tiles: vec![],
+            unspawned_tiles,
+            river_tiles,
+            river_end: None,
+            last_river_turn: None,
+            discarded_tiles: vec![],
+            board_tile_matrix: Default::default(),
+            board_tile_matrix_inverse: Default::default(),
+            board_tile_directions: Default::default(),
+            next_placeholder_index: PLACEHOLDER_TILE_OFFSET,
+            board_area_graph: Default::default(),
+            area_index_to_area_graph_index: Default::default(),
+            area_meeples: Default::default(),
+            last_placed_tile: None,
+            extra_turn_earned: false,
+            in_extra_turn: false,
+            dragon: None,
+            dragon_path: vec![],
+            dragon_moves_left: 0,
+            set_aside_tiles: vec![],
+            fairy: None,
+            abbey_tiles,
+            pending_wagons: vec![],
+            towers: Default::default(),
+            tower_built: None,
+            sheep_bag: create_sheep_bag(),
+            flocks: Default::default(),
+            bridge_requested: false,
+            castles: vec![],
+            pending_castles: vec![],
+            bazaar_pending: false,
+            playing_bought_tile: false,
+            market: vec![],
+            bazaar_players: vec![],
+            auction: None,
+        };
+    }
+
+    pub fn all_tiles_drawn(&self) -> bool {
+        return self.unspawned_tiles.is_empty() && self.river_tiles.is_empty();
+    }
+
+    // Tile placed at coords, if there is one. Placeholders don't count.
+    pub fn get_placed_tile(&self, coords: TileMatrixCoords) -> Option<TileIndex> {
+        match self.board_tile_matrix.get(&coords) {
+            Some(&tile_idx) if tile_idx < PLACEHOLDER_TILE_OFFSET => Some(tile_idx),
+            _ => None,
+        }
+    }
+
+    // Empty spots next to placed tiles, where the next tile can go. Just the origin until the
+    // first tile is down.
+    pub fn get_frontier(&self) -> Vec<TileMatrixCoords> {
+        let mut frontier: Vec<TileMatrixCoords> = vec![];
+        for (coords, tile_idx) in &self.board_tile_matrix {
+            if *tile_idx >= PLACEHOLDER_TILE_OFFSET {
+                continue;
+            }
+            for side in TILE_SIDES {
+                let neighbor_coords = get_neighbor_coords(*coords, side);
+                if self.get_placed_tile(neighbor_coords).is_none()
+                    && !frontier.contains(&neighbor_coords)
+                {
+                    frontier.push(neighbor_coords);
+                }
+            }
+        }
+        if frontier.is_empty()
+            && self
+                .get_placed_tile(TileMatrixCoords { x: 0, y: 0 })
+                .is_none()
+        {
+            frontier.push(TileMatrixCoords { x: 0, y: 0 });
+        }
+        frontier.sort_by_key(|c| (c.x, c.y));
+        return frontier;
+    }
+
+    // Every area on the board connected to this one across tiles, including itself. Empty if
+    // the area isn't on the board.
+    pub fn get_connected_areas(&self, area_idx: TileAreaIndex) -> Vec<TileAreaIndex> {
+        let Some(&start) = self.area_index_to_area_graph_index.get(&area_idx) else {
+            return vec![];
+        };
+        let mut connected_areas: Vec<TileAreaIndex> = vec![];
+        let mut bfs = Bfs::new(&self.board_area_graph, start);
+        while let Some(node) = bfs.next(&self.board_area_graph) {
+            connected_areas.push(self.board_area_graph[node]);
+        }
+        return connected_areas;
+    }
+}
+
+// Everything in the game state, for debugging.
+impl fmt::Display for GameplayData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "seed: {:?}", self.seed)?;
+        writeln!(f, "spawned_tiles: {:?}", self.spawned_tiles)?;
+        writeln!(f, "unspawned_tiles: {:?}", self.unspawned_tiles)?;
+        writeln!(f, "discarded_tiles: {:?}", self.discarded_tiles)?;
+        writeln!(f, "river_tiles: {:?}", self.river_tiles)?;
+        writeln!(f, "river_end: {:?}", self.river_end)?;
+        writeln!(f, "last_river_turn: {:?}", self.last_river_turn)?;
+        writeln!(
+            f,
+            "next_placeholder_index: {:?}",
+            self.next_placeholder_index
+        )?;
+        // println!(
+        //     "tile_index_to_tile_graph_index: {:?}",
+        //     self.tile_index_to_tile_graph_index
+        // );
+        writeln!(f, "board_tile_matrix: {:?}", self.board_tile_matrix)?;
+        writeln!(
+            f,
+            "board_tile_matrix_inverse: {:?}",
+            self.board_tile_matrix_inverse
+        )?;
+        writeln!(f, "board_tile_directions: {:?}", self.board_tile_directions)?;
+        writeln!(
+            f,
+            "area_index_to_area_graph_index: {:?}",
+            self.area_index_to_area_graph_index
+        )?;
+        writeln!(f, "area_meeples: {:?}", self.area_meeples)?;
+        writeln!(f, "last_placed_tile: {:?}", self.last_placed_tile)?;
+        writeln!(f, "extra_turn_earned: {:?}", self.extra_turn_earned)?;
+        writeln!(f, "in_extra_turn: {:?}", self.in_extra_turn)?;
+        writeln!(f, "dragon: {:?}", self.dragon)?;
+        writeln!(f, "dragon_path: {:?}", self.dragon_path)?;
+        writeln!(f, "dragon_moves_left: {:?}", self.dragon_moves_left)?;
+        writeln!(f, "set_aside_tiles: {:?}", self.set_aside_tiles)?;
+        writeln!(f, "fairy: {:?}", self.fairy)?;
+        writeln!(f, "abbey_tiles: {:?}", self.abbey_tiles)?;
+        writeln!(f, "pending_wagons: {:?}", self.pending_wagons)?;
+        writeln!(f, "towers: {:?}", self.towers)?;
+        writeln!(f, "tower_built: {:?}", self.tower_built)?;
+        writeln!(f, "sheep_bag: {:?}", self.sheep_bag)?;
+        writeln!(f, "flocks: {:?}", self.flocks)?;
+        writeln!(f, "bridge_requested: {:?}", self.bridge_requested)?;
+        writeln!(f, "castles: {:?}", self.castles)?;
+        writeln!(f, "pending_castles: {:?}", self.pending_castles)?;
+        writeln!(f, "bazaar_pending: {:?}", self.bazaar_pending)?;
+        writeln!(f, "playing_bought_tile: {:?}", self.playing_bought_tile)?;
+        writeln!(f, "market: {:?}", self.market)?;
+        writeln!(f, "bazaar_players: {:?}", self.bazaar_players)?;
+        writeln!(f, "auction: {:?}", self.auction)?;
+        // println!(
+        //     "board_tile_graph:\n{:?}",
+        //     Dot::with_config(&self.board_tile_matrix, &[])
+        // );
+        writeln!(
+            f,
+            "board_area_graph:\n{:?}",
+            Dot::with_config(&self.board_area_graph, &[])
+        )?;
+        return Ok(());
+    }
+}
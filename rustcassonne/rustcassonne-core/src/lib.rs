@@ -0,0 +1,10 @@
+#![allow(clippy::needless_return)]
+
+// Rules of the game as plain data and functions, with no rendering or input. The Bevy app in
+// the parent crate is one frontend over it.
+
+pub mod game_board;
+pub mod players;
+pub mod rules;
+pub mod scoring;
+pub mod tiles;
@@ -0,0 +1,292 @@
+use crate::tiles::*;
+
+pub type MeepleIndex = usize;
+pub type PlayerIndex = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TeamColor {
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Black,
+    Gray,
+    Pink,
+}
+
+// Handed out to players in seating order.
+pub const TEAM_COLORS: [TeamColor; MAX_PLAYERS] = [
+    TeamColor::Blue,
+    TeamColor::Red,
+    TeamColor::Green,
+    TeamColor::Yellow,
+    TeamColor::Black,
+    TeamColor::Gray,
+];
+
+// Inns & Cathedrals brings meeples for one more player.
+pub const EXTRA_TEAM_COLOR: TeamColor = TeamColor::Pink;
+
+// Colors players can pick from with the given expansions.
+pub fn team_colors(expansions: &[Expansion]) -> Vec<TeamColor> {
+    let mut colors = TEAM_COLORS.to_vec();
+    if expansions.contains(&Expansion::InnsAndCathedrals) {
+        colors.push(EXTRA_TEAM_COLOR);
+    }
+    return colors;
+}
+
+pub fn max_players(expansions: &[Expansion]) -> usize {
+    return team_colors(expansions).len();
+}
+
+// Figures other than normal meeples get their own indices, after every normal meeple.
+pub const LARGE_MEEPLE_OFFSET: MeepleIndex = 1000;
+pub const BUILDER_OFFSET: MeepleIndex = 1100;
+pub const PIG_OFFSET: MeepleIndex = 1200;
+pub const MAYOR_OFFSET: MeepleIndex = 1300;
+pub const WAGON_OFFSET: MeepleIndex = 1400;
+pub const BARN_OFFSET: MeepleIndex = 1500;
+pub const SHEPHERD_OFFSET: MeepleIndex = 1600;
+pub const ABBOT_OFFSET: MeepleIndex = 1700;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FigureKind {
+    Meeple,
+    LargeMeeple,
+    Builder,
+    Pig,
+    Mayor,
+    Wagon,
+    Barn,
+    Shepherd,
+    Abbot,
+}
+
+pub fn figure_kind(meeple: MeepleIndex) -> FigureKind {
+    if meeple >= ABBOT_OFFSET {
+        return FigureKind::Abbot;
+    }
+    if meeple >= SHEPHERD_OFFSET {
+        return FigureKind::Shepherd;
+    }
+    if meeple >= BARN_OFFSET {
+        return FigureKind::Barn;
+    }
+    if meeple >= WAGON_OFFSET {
+        return FigureKind::Wagon;
+    }
+    if meeple >= MAYOR_OFFSET {
+        return FigureKind::Mayor;
+    }
+    if meeple >= PIG_OFFSET {
+        return FigureKind::Pig;
+    }
+    if meeple >= BUILDER_OFFSET {
+        return FigureKind::Builder;
+    }
+    if meeple >= LARGE_MEEPLE_OFFSET {
+        return FigureKind::LargeMeeple;
+    }
+    return FigureKind::Meeple;
+}
+
+pub fn large_meeple_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return LARGE_MEEPLE_OFFSET + player_idx;
+}
+
+pub fn builder_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return BUILDER_OFFSET + player_idx;
+}
+
+pub fn pig_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return PIG_OFFSET + player_idx;
+}
+
+pub fn mayor_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return MAYOR_OFFSET + player_idx;
+}
+
+pub fn wagon_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return WAGON_OFFSET + player_idx;
+}
+
+pub fn barn_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return BARN_OFFSET + player_idx;
+}
+
+pub fn shepherd_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return SHEPHERD_OFFSET + player_idx;
+}
+
+pub fn abbot_index(player_idx: PlayerIndex) -> MeepleIndex {
+    return ABBOT_OFFSET + player_idx;
+}
+
+// Figures that claim the feature they stand on.
+pub fn figure_is_follower(meeple: MeepleIndex) -> bool {
+    return matches!(
+        figure_kind(meeple),
+        FigureKind::Meeple
+            | FigureKind::LargeMeeple
+            | FigureKind::Mayor
+            | FigureKind::Wagon
+            | FigureKind::Abbot
+    );
+}
+
+pub fn meeple_is_large(meeple: MeepleIndex) -> bool {
+    return figure_kind(meeple) == FigureKind::LargeMeeple;
+}
+
+// How much the figure counts when deciding who owns a feature. A large meeple counts as
+// two, builders, pigs, barns and shepherds don't count at all. Mayors count the pennants of their
+// town, see get_feature_owners.
+pub fn meeple_strength(meeple: MeepleIndex) -> usize {
+    match figure_kind(meeple) {
+        FigureKind::Meeple | FigureKind::Wagon | FigureKind::Abbot => 1,
+        FigureKind::LargeMeeple => 2,
+        FigureKind::Builder
+        | FigureKind::Pig
+        | FigureKind::Mayor
+        | FigureKind::Barn
+        | FigureKind::Shepherd => 0,
+    }
+}
+
+// A meeple standing on an area of the board.
+#[derive(Clone, Copy, Debug)]
+pub struct PlacedMeeple {
+    pub meeple: MeepleIndex,
+    pub player: PlayerIndex,
+}
+
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub name: String,
+    pub team: TeamColor,
+    pub meeples: Vec<MeepleIndex>,
+    pub points: i32,
+    // Goods tokens, indexed by Goods.
+    pub goods: [usize; NUM_GOODS],
+    // Abbey & Mayor: the abbey tile can be played once, instead of drawing.
+    pub has_abbey: bool,
+    // Tower: pieces left to build towers with, and other players' meeples held captive.
+    pub tower_pieces: usize,
+    pub prisoners: Vec<PlacedMeeple>,
+    // Bridges, Castles & Bazaars: bridges left, and tiles bought at a bazaar still to play.
+    pub bridges: usize,
+    pub bought_tiles: Vec<TileIndex>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Players {
+    pub players: Vec<Player>,
+    pub current_player: PlayerIndex,
+}
+
+impl Players {
+    // Seats the players in the given order, each with NUM_MEEPLES meeples and the color they
+    // picked, or else the first one nobody picked. Inns & Cathedrals adds a large meeple each,
+    // Traders & Builders a builder and a pig, Abbey & Mayor an abbey, a mayor, a wagon and a
+    // barn, Tower a share of the tower pieces, Hills & Sheep a shepherd, Bridges, Castles &
+    // Bazaars three bridges, Gardens an abbot.
+    pub fn new(
+        names: &[String],
+        picked_colors: &[Option<TeamColor>],
+        expansions: &[Expansion],
+    ) -> Players {
+        let max_players = max_players(expansions);
+        assert!(
+            (MIN_PLAYERS..=max_players).contains(&names.len()),
+            "need {}-{} players, got {}",
+            MIN_PLAYERS,
+            max_players,
+            names.len()
+        );
+        let mut free_colors: Vec<TeamColor> = team_colors(expansions)
+            .into_iter()
+            .filter(|c| !picked_colors.contains(&Some(*c)))
+            .collect();
+        free_colors.reverse();
+        let mut players: Vec<Player> = vec![];
+        for (player_idx, name) in names.iter().enumerate() {
+            let team = match picked_colors.get(player_idx) {
+                Some(Some(color)) => *color,
+                _ => free_colors.pop().unwrap(),
+            };
+            let mut meeples: Vec<MeepleIndex> =
+                (player_idx * NUM_MEEPLES..(player_idx + 1) * NUM_MEEPLES).collect();
+            if expansions.contains(&Expansion::InnsAndCathedrals) {
+                meeples.push(large_meeple_index(player_idx));
+            }
+            if expansions.contains(&Expansion::TradersAndBuilders) {
+                meeples.push(builder_index(player_idx));
+                meeples.push(pig_index(player_idx));
+            }
+            let abbey_and_mayor = expansions.contains(&Expansion::AbbeyAndMayor);
+            let tower_pieces = if expansions.contains(&Expansion::Tower) {
+                tower_pieces_per_player(names.len())
+            } else {
+                0
+            };
+            if abbey_and_mayor {
+                meeples.push(mayor_index(player_idx));
+                meeples.push(wagon_index(player_idx));
+                meeples.push(barn_index(player_idx));
+            }
+            if expansions.contains(&Expansion::HillsAndSheep) {
+                meeples.push(shepherd_index(player_idx));
+            }
+            if expansions.contains(&Expansion::GardensAndAbbot) {
+                meeples.push(abbot_index(player_idx));
+            }
+            players.push(Player {
+                name: name.clone(),
+                team,
+                meeples,
+                points: 0,
+                goods: [0; NUM_GOODS],
+                has_abbey: abbey_and_mayor,
+                tower_pieces,
+                prisoners: vec![],
+                bridges: if expansions.contains(&Expansion::BridgesCastlesBazaars) {
+                    NUM_BRIDGES
+                } else {
+                    0
+                },
+                bought_tiles: vec![],
+            });
+        }
+        return Players {
+            players,
+            current_player: 0,
+        };
+    }
+
+    pub fn current(&self) -> &Player {
+        return &self.players[self.current_player];
+    }
+
+    pub fn advance_turn(&mut self) {
+        self.current_player = (self.current_player + 1) % self.players.len();
+    }
+}
+
+// Fewer players get more of the tower pieces each.
+pub fn tower_pieces_per_player(num_players: usize) -> usize {
+    match num_players {
+        2 => 10,
+        3 => 9,
+        4 => 7,
+        5 => 6,
+        6 => 5,
+        _ => 4,
+    }
+}
+
+pub const NUM_MEEPLES: usize = 7;
+pub const NUM_BRIDGES: usize = 3;
+pub const MIN_PLAYERS: usize = 2;
+// Without the extra color of Inns & Cathedrals.
+pub const MAX_PLAYERS: usize = 6;
@@ -0,0 +1,3065 @@
+use rand::Rng;
+
+use crate::game_board::*;
+use crate::players::*;
+use crate::tiles::*;
+
+// Checks the tile against every real tile around coords. Empty and placeholder spots accept
+// anything.
+pub fn tile_fits_at(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) -> bool {
+    if tile_type_is_river(tile_data.all_tiles[tile_idx].tile_type)
+        && !river_tile_fits_at(gameplay_data, tile_data, tile_idx, dir, coords)
+    {
+        return false;
+    }
+    if tile_type_is_abbey(tile_data.all_tiles[tile_idx].tile_type) {
+        return TILE_SIDES.iter().all(|side| {
+            gameplay_data
+                .get_placed_tile(get_neighbor_coords(coords, *side))
+                .is_some()
+        });
+    }
+    let edge_areas = get_rotated_edge_areas(&tile_data.all_tiles[tile_idx], dir, tile_data);
+    let edge_types = edge_areas.map(|a| tile_data.all_areas[a].area_type);
+    return edges_fit_at(gameplay_data, tile_data, edge_types, coords);
+}
+
+// Checks the area types along the edges of a tile against every real tile around coords.
+fn edges_fit_at(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    edge_types: [AreaType; 12],
+    coords: TileMatrixCoords,
+) -> bool {
+    for side in TILE_SIDES {
+        let Some(neighbor_idx) = gameplay_data.get_placed_tile(get_neighbor_coords(coords, side))
+        else {
+            continue;
+        };
+        let neighbor_edge_areas = get_rotated_edge_areas(
+            &tile_data.all_tiles[neighbor_idx],
+            gameplay_data.board_tile_directions[&neighbor_idx],
+            tile_data,
+        );
+        for edge in side_edges(side) {
+            let neighbor_area_type =
+                tile_data.all_areas[neighbor_edge_areas[opposite_edge(edge)]].area_type;
+            if !area_types_connect(edge_types[edge], neighbor_area_type) {
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
+// Sides of the turned tile the river runs through.
+pub fn get_river_sides(tile: &Tile, dir: TileDirection, tile_data: &GameTileData) -> Vec<TileSide> {
+    let side_areas = get_rotated_side_areas(tile, dir, tile_data);
+    return TILE_SIDES
+        .into_iter()
+        .filter(|side| {
+            tile_data.all_areas[side_areas[*side as usize][1]].area_type == AreaType::Water
+        })
+        .collect();
+}
+
+// Which way the river bends when it comes in heading one way and leaves by exit_side.
+pub fn get_river_turn(heading: TileSide, exit_side: TileSide) -> Option<RiverTurn> {
+    if exit_side as usize == (heading as usize + 1) % 4 {
+        return Some(RiverTurn::Right);
+    }
+    if exit_side as usize == (heading as usize + 3) % 4 {
+        return Some(RiverTurn::Left);
+    }
+    return None;
+}
+
+// A river tile has to carry on from the open end of the river, and can't bend the same way
+// as the last bend or the river would head back into itself.
+fn river_tile_fits_at(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) -> bool {
+    let Some((end_coords, end_side)) = gameplay_data.river_end else {
+        return false;
+    };
+    if coords != get_neighbor_coords(end_coords, end_side) {
+        return false;
+    }
+    let river_sides = get_river_sides(&tile_data.all_tiles[tile_idx], dir, tile_data);
+    if !river_sides.contains(&opposite_side(end_side)) {
+        return false;
+    }
+    let Some(exit_side) = river_sides
+        .into_iter()
+        .find(|side| *side != opposite_side(end_side))
+    else {
+        // The lake.
+        return true;
+    };
+    let turn = get_river_turn(end_side, exit_side);
+    return turn.is_none() || turn != gameplay_data.last_river_turn;
+}
+
+// Moves the open end of the river onto a river tile just placed. The spring starts the river
+// and the lake closes it.
+fn extend_river(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) {
+    let river_sides = get_river_sides(&tile_data.all_tiles[tile_idx], dir, tile_data);
+    let exit_side = match gameplay_data.river_end {
+        None => river_sides.first().copied(),
+        Some((_, end_side)) => river_sides
+            .into_iter()
+            .find(|side| *side != opposite_side(end_side)),
+    };
+    if let (Some((_, end_side)), Some(exit_side)) = (gameplay_data.river_end, exit_side) {
+        if let Some(turn) = get_river_turn(end_side, exit_side) {
+            gameplay_data.last_river_turn = Some(turn);
+        }
+    }
+    gameplay_data.river_end = exit_side.map(|side| (coords, side));
+}
+
+// Every spot on the frontier and rotation where the tile fits, worked out from the board
+// alone.
+pub fn get_legal_placements(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+) -> Vec<(TileMatrixCoords, TileDirection)> {
+    let mut placements: Vec<(TileMatrixCoords, TileDirection)> = vec![];
+    for coords in gameplay_data.get_frontier() {
+        for dir in TILE_DIRECTIONS {
+            if tile_fits_at(gameplay_data, tile_data, tile_idx, dir, coords) {
+                placements.push((coords, dir));
+            }
+        }
+    }
+    return placements;
+}
+
+// Whether the current player can play the tile somewhere, counting spots where only one of
+// their bridges would make it fit.
+pub fn tile_can_be_placed(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+    tile_idx: TileIndex,
+) -> bool {
+    if !get_legal_placements(gameplay_data, tile_data, tile_idx).is_empty() {
+        return true;
+    }
+    if players.current().bridges == 0 {
+        return false;
+    }
+    return gameplay_data.get_frontier().into_iter().any(|coords| {
+        TILE_DIRECTIONS.iter().any(|dir| {
+            get_bridge_for_fit(gameplay_data, tile_data, players, tile_idx, *dir, coords).is_some()
+        })
+    });
+}
+
+// Takes a random tile out of the bag, using the game's seeded rng, once the river stack is
+// used up. Tiles the current player can't place anywhere go on the discard pile and another
+// one is drawn, as the rules say. None once the bag is empty. Dragon tiles drawn before the
+// dragon is out are set aside.
+pub fn draw_tile(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+) -> Option<TileIndex> {
+    loop {
+        let next_tile: TileIndex = if !gameplay_data.river_tiles.is_empty() {
+            draw_river_tile(gameplay_data, tile_data, players)
+        } else {
+            take_random_bag_tile(gameplay_data)?
+        };
+        if gameplay_data.dragon.is_none()
+            && tile_type_has_dragon(tile_data.all_tiles[next_tile].tile_type)
+        {
+            gameplay_data.set_aside_tiles.push(next_tile);
+            continue;
+        }
+        if !tile_can_be_placed(gameplay_data, tile_data, players, next_tile) {
+            gameplay_data.discarded_tiles.push(next_tile);
+            continue;
+        }
+        return Some(next_tile);
+    }
+}
+
+// Any tile out of the bag, using the game's seeded rng.
+fn take_random_bag_tile(gameplay_data: &mut GameplayData) -> Option<TileIndex> {
+    if gameplay_data.unspawned_tiles.is_empty() {
+        return None;
+    }
+    let next_tile_index: usize = gameplay_data
+        .rng
+        .gen_range(0..gameplay_data.unspawned_tiles.len());
+    return Some(gameplay_data.unspawned_tiles.remove(next_tile_index));
+}
+
+// The lake closes the river, so it stays at the bottom of the river stack. A river tile that
+// can't carry the river on right now goes back in the stack and another one is drawn. Only
+// when none of them fits does one get drawn anyway, to be discarded.
+fn draw_river_tile(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+) -> TileIndex {
+    let river_tile_indexes: Vec<usize> = (0..gameplay_data.river_tiles.len())
+        .filter(|i| {
+            tile_data.all_tiles[gameplay_data.river_tiles[*i]].tile_type != RIVER_END_TILE_TYPE
+        })
+        .collect();
+    if river_tile_indexes.is_empty() {
+        return gameplay_data.river_tiles.remove(0);
+    }
+    let placeable_indexes: Vec<usize> = river_tile_indexes
+        .iter()
+        .copied()
+        .filter(|i| {
+            tile_can_be_placed(
+                gameplay_data,
+                tile_data,
+                players,
+                gameplay_data.river_tiles[*i],
+            )
+        })
+        .collect();
+    let river_tile_indexes = if placeable_indexes.is_empty() {
+        river_tile_indexes
+    } else {
+        placeable_indexes
+    };
+    let next_tile_index =
+        river_tile_indexes[gameplay_data.rng.gen_range(0..river_tile_indexes.len())];
+    return gameplay_data.river_tiles.remove(next_tile_index);
+}
+
+// Puts the tile on the board at coords, taking the place of the placeholder there if any,
+// and surrounds it with new placeholders. Returns the placeholders that were added so the
+// frontend can show them.
+pub fn place_tile(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) -> Vec<(TileMatrixCoords, TileIndex)> {
+    if gameplay_data
+        .board_tile_matrix_inverse
+        .contains_key(&tile_idx)
+    {
+        panic!(
+            "tile_idx {:?} already in matrix:\n{:?}",
+            tile_idx, gameplay_data.board_tile_matrix_inverse
+        );
+    }
+    if let Some(placeholder_idx) = gameplay_data.board_tile_matrix.insert(coords, tile_idx) {
+        if placeholder_idx < PLACEHOLDER_TILE_OFFSET {
+            panic!("{:?} already has tile {:?}", coords, placeholder_idx);
+        }
+        gameplay_data
+            .board_tile_matrix_inverse
+            .remove(&placeholder_idx);
+    }
+    gameplay_data
+        .board_tile_matrix_inverse
+        .insert(tile_idx, coords);
+    gameplay_data.board_tile_directions.insert(tile_idx, dir);
+    add_tile_to_area_graph(gameplay_data, tile_data, tile_idx, dir, coords);
+    if tile_type_is_river(tile_data.all_tiles[tile_idx].tile_type) {
+        extend_river(gameplay_data, tile_data, tile_idx, dir, coords);
+    }
+    if tile_type_has_volcano(tile_data.all_tiles[tile_idx].tile_type) {
+        gameplay_data.dragon = Some(coords);
+        let mut set_aside_tiles = std::mem::take(&mut gameplay_data.set_aside_tiles);
+        gameplay_data.unspawned_tiles.append(&mut set_aside_tiles);
+    }
+    let bought = std::mem::take(&mut gameplay_data.playing_bought_tile);
+    if tile_type_has_bazaar(tile_data.all_tiles[tile_idx].tile_type) && !bought {
+        gameplay_data.bazaar_pending = true;
+    }
+    gameplay_data.last_placed_tile = Some(tile_idx);
+
+    let mut new_placeholders: Vec<(TileMatrixCoords, TileIndex)> = vec![];
+    for side in TILE_SIDES {
+        let new_coords = get_neighbor_coords(coords, side);
+        if !gameplay_data.board_tile_matrix.contains_key(&new_coords) {
+            let next_placeholder_index = gameplay_data.next_placeholder_index;
+            gameplay_data.next_placeholder_index += 1;
+            gameplay_data
+                .board_tile_matrix
+                .insert(new_coords, next_placeholder_index);
+            gameplay_data
+                .board_tile_matrix_inverse
+                .insert(next_placeholder_index, new_coords);
+            new_placeholders.push((new_coords, next_placeholder_index));
+        }
+    }
+    return new_placeholders;
+}
+
+// Every game starts from one of these, town side up.
+pub const START_TILE_TYPE: TileType = TileType::RFRT_02;
+// Both the spring and the lake of the river.
+pub const RIVER_END_TILE_TYPE: TileType = TileType::FWFF;
+
+fn take_tile_of_type(
+    tiles: &mut Vec<TileIndex>,
+    tile_data: &GameTileData,
+    tile_type: TileType,
+) -> TileIndex {
+    let Some(idx) = tiles
+        .iter()
+        .position(|t| tile_data.all_tiles[*t].tile_type == tile_type)
+    else {
+        panic!("no {:?} in the bag to start with", tile_type);
+    };
+    return tiles.remove(idx);
+}
+
+// Takes the start tile out of the bag and puts it at the origin, or the spring when playing
+// with the river. Returns it along with the placeholders around it.
+pub fn place_start_tile(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+) -> (TileIndex, Vec<(TileMatrixCoords, TileIndex)>) {
+    let tile_idx = if !gameplay_data.river_tiles.is_empty() {
+        take_tile_of_type(
+            &mut gameplay_data.river_tiles,
+            tile_data,
+            RIVER_END_TILE_TYPE,
+        )
+    } else {
+        take_tile_of_type(
+            &mut gameplay_data.unspawned_tiles,
+            tile_data,
+            START_TILE_TYPE,
+        )
+    };
+    gameplay_data.spawned_tiles.push(tile_idx);
+    let new_placeholders = place_tile(
+        gameplay_data,
+        tile_data,
+        tile_idx,
+        TileDirection::UP,
+        TileMatrixCoords { x: 0, y: 0 },
+    );
+    // Nobody played it, so nobody can put a meeple on it.
+    gameplay_data.last_placed_tile = None;
+    return (tile_idx, new_placeholders);
+}
+
+// Adds the tile's areas to the area graph and links them to the areas they touch on
+// neighboring tiles.
+fn add_tile_to_area_graph(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) {
+    for area_idx in &tile_data.all_tiles[tile_idx].areas {
+        let node = gameplay_data.board_area_graph.add_node(*area_idx);
+        gameplay_data
+            .area_index_to_area_graph_index
+            .insert(*area_idx, node);
+    }
+
+    let edge_areas = get_rotated_edge_areas(&tile_data.all_tiles[tile_idx], dir, tile_data);
+    for side in TILE_SIDES {
+        let Some(neighbor_idx) = gameplay_data.get_placed_tile(get_neighbor_coords(coords, side))
+        else {
+            continue;
+        };
+        let neighbor_edge_areas = get_rotated_edge_areas(
+            &tile_data.all_tiles[neighbor_idx],
+            gameplay_data.board_tile_directions[&neighbor_idx],
+            tile_data,
+        );
+        for edge in side_edges(side) {
+            let area_idx = edge_areas[edge];
+            let neighbor_area_idx = neighbor_edge_areas[opposite_edge(edge)];
+            // Abbey walls only border the areas they touch.
+            if !area_types_connect(
+                tile_data.all_areas[area_idx].area_type,
+                tile_data.all_areas[neighbor_area_idx].area_type,
+            ) {
+                continue;
+            }
+            let node = gameplay_data.area_index_to_area_graph_index[&area_idx];
+            let neighbor_node = gameplay_data.area_index_to_area_graph_index[&neighbor_area_idx];
+            // Several edges of a side can belong to the same pair of areas.
+            gameplay_data
+                .board_area_graph
+                .update_edge(node, neighbor_node, ());
+        }
+    }
+}
+
+// Whether every edge of a placed area has a real tile on the other side.
+pub fn area_is_closed(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let area = &tile_data.all_areas[area_idx];
+    let coords = gameplay_data.board_tile_matrix_inverse[&area.tile_idx];
+    let dir = gameplay_data.board_tile_directions[&area.tile_idx];
+    for edge in &area.edges {
+        let side = edge_side(rotate_edge(*edge, dir));
+        if gameplay_data
+            .get_placed_tile(get_neighbor_coords(coords, side))
+            .is_none()
+        {
+            return false;
+        }
+    }
+    return true;
+}
+
+// The cloister's own tile followed by every placed tile around it.
+pub fn get_cloister_tiles(
+    gameplay_data: &GameplayData,
+    coords: TileMatrixCoords,
+) -> Vec<TileIndex> {
+    let mut tiles: Vec<TileIndex> = vec![gameplay_data.board_tile_matrix[&coords]];
+    for offset in SURROUNDING_COORDS {
+        let surrounding_coords = TileMatrixCoords {
+            x: coords.x + offset.x,
+            y: coords.y + offset.y,
+        };
+        if let Some(tile_idx) = gameplay_data.get_placed_tile(surrounding_coords) {
+            tiles.push(tile_idx);
+        }
+    }
+    return tiles;
+}
+
+// Number of placed tiles around the cloister, for scoring unfinished cloisters.
+pub fn count_cloister_neighbors(gameplay_data: &GameplayData, coords: TileMatrixCoords) -> usize {
+    return get_cloister_tiles(gameplay_data, coords).len() - 1;
+}
+
+// Cloisters on the tile or any of the eight around it that are now fully surrounded. Only
+// the tile that fills the last spot can complete a cloister, so each is reported once.
+fn find_completed_cloisters(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+) -> Vec<Feature> {
+    let mut completed_cloisters: Vec<Feature> = vec![];
+    let coords = gameplay_data.board_tile_matrix_inverse[&tile_idx];
+    for offset in [TileMatrixCoords { x: 0, y: 0 }]
+        .iter()
+        .chain(SURROUNDING_COORDS.iter())
+    {
+        let cloister_coords = TileMatrixCoords {
+            x: coords.x + offset.x,
+            y: coords.y + offset.y,
+        };
+        let Some(cloister_tile_idx) = gameplay_data.get_placed_tile(cloister_coords) else {
+            continue;
+        };
+        for area_idx in &tile_data.all_tiles[cloister_tile_idx].areas {
+            if area_type_to_feature_type(tile_data.all_areas[*area_idx].area_type)
+                != Some(FeatureType::Cloister)
+            {
+                continue;
+            }
+            let tiles = get_cloister_tiles(gameplay_data, cloister_coords);
+            if tiles.len() == SURROUNDING_COORDS.len() + 1 {
+                completed_cloisters.push(Feature {
+                    feature_type: FeatureType::Cloister,
+                    tiles,
+                    areas: vec![*area_idx],
+                });
+            }
+        }
+    }
+    return completed_cloisters;
+}
+
+// The road, town or farm the area belongs to, following the area graph across tiles.
+pub fn get_feature(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    feature_type: FeatureType,
+    area_idx: TileAreaIndex,
+) -> Feature {
+    let areas = gameplay_data.get_connected_areas(area_idx);
+    let mut tiles: Vec<TileIndex> = areas
+        .iter()
+        .map(|a| tile_data.all_areas[*a].tile_idx)
+        .collect();
+    tiles.sort();
+    tiles.dedup();
+    return Feature {
+        feature_type,
+        tiles,
+        areas,
+    };
+}
+
+// Whether a road or town has no open edges left.
+pub fn feature_is_closed(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    feature: &Feature,
+) -> bool {
+    return feature
+        .areas
+        .iter()
+        .all(|a| area_is_closed(gameplay_data, tile_data, *a));
+}
+
+// Areas whose features the tile may have finished. An abbey has no roads or towns of its
+// own, so for it those are the neighbours' areas on the edges its walls close.
+fn get_areas_closed_by_tile(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+) -> Vec<TileAreaIndex> {
+    let mut areas = tile_data.all_tiles[tile_idx].areas.clone();
+    if !tile_type_is_abbey(tile_data.all_tiles[tile_idx].tile_type) {
+        return areas;
+    }
+    let coords = gameplay_data.board_tile_matrix_inverse[&tile_idx];
+    for side in TILE_SIDES {
+        let Some(neighbor_idx) = gameplay_data.get_placed_tile(get_neighbor_coords(coords, side))
+        else {
+            continue;
+        };
+        let neighbor_edge_areas = get_rotated_edge_areas(
+            &tile_data.all_tiles[neighbor_idx],
+            gameplay_data.board_tile_directions[&neighbor_idx],
+            tile_data,
+        );
+        for edge in side_edges(side) {
+            areas.push(neighbor_edge_areas[opposite_edge(edge)]);
+        }
+    }
+    return areas;
+}
+
+// Roads, towns and cloisters the tile finished off. Roads end at EndRoad areas or loop back
+// on themselves, so both fall out of the same open edge check.
+pub fn find_completed_features(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+) -> Vec<Feature> {
+    let mut completed_features: Vec<Feature> = vec![];
+    let mut checked_areas: Vec<TileAreaIndex> = vec![];
+    for area_idx in &get_areas_closed_by_tile(gameplay_data, tile_data, tile_idx) {
+        if checked_areas.contains(area_idx) {
+            continue;
+        }
+        let Some(feature_type) =
+            area_type_to_feature_type(tile_data.all_areas[*area_idx].area_type)
+        else {
+            continue;
+        };
+        // Cloisters are checked separately and farms never complete.
+        if feature_type == FeatureType::Cloister || feature_type == FeatureType::Farm {
+            continue;
+        }
+
+        let feature = get_feature(gameplay_data, tile_data, feature_type, *area_idx);
+        checked_areas.extend(&feature.areas);
+        if feature_is_closed(gameplay_data, tile_data, &feature) {
+            completed_features.push(feature);
+        }
+    }
+    completed_features.extend(find_completed_cloisters(gameplay_data, tile_data, tile_idx));
+    return completed_features;
+}
+
+// Nothing can be put on the tile the dragon is on.
+fn area_is_under_dragon(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let tile_idx = tile_data.all_areas[area_idx].tile_idx;
+    return gameplay_data.dragon.is_some()
+        && gameplay_data.board_tile_matrix_inverse.get(&tile_idx) == gameplay_data.dragon.as_ref();
+}
+
+// Whether a meeple may go on the area: it has to be free and claimable, and no follower can
+// be on the road, town, field or cloister it belongs to yet. Builders, pigs, barns and
+// shepherds don't claim anything, so they don't keep meeples out. Gardens are for abbots
+// only.
+pub fn meeple_can_be_placed(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let area_type = tile_data.all_areas[area_idx].area_type;
+    if !area_type_is_claimable(area_type)
+        || area_type == AreaType::Garden
+        || area_is_under_dragon(gameplay_data, tile_data, area_idx)
+    {
+        return false;
+    }
+    return !gameplay_data.area_meeples.contains_key(&area_idx)
+        && !feature_has_figure(gameplay_data, area_idx, |m| figure_is_follower(m.meeple));
+}
+
+fn feature_has_figure(
+    gameplay_data: &GameplayData,
+    area_idx: TileAreaIndex,
+    wanted: impl Fn(&PlacedMeeple) -> bool,
+) -> bool {
+    return gameplay_data
+        .get_connected_areas(area_idx)
+        .iter()
+        .filter_map(|a| gameplay_data.area_meeples.get(a))
+        .any(wanted);
+}
+
+// Meeples follow meeple_can_be_placed, and so do mayors in towns and wagons on roads, towns
+// and cloisters. A builder joins a road or town, and a pig a field, where its owner already
+// has a follower. A barn or a shepherd goes in a field without one. An abbot goes in an empty
+// cloister or garden.
+pub fn figure_can_be_placed(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+    placed_meeple: PlacedMeeple,
+) -> bool {
+    let feature_type = area_type_to_feature_type(tile_data.all_areas[area_idx].area_type);
+    let allowed_feature_types = match figure_kind(placed_meeple.meeple) {
+        FigureKind::Meeple | FigureKind::LargeMeeple => {
+            return meeple_can_be_placed(gameplay_data, tile_data, area_idx);
+        }
+        FigureKind::Mayor => {
+            return feature_type == Some(FeatureType::Town)
+                && meeple_can_be_placed(gameplay_data, tile_data, area_idx);
+        }
+        FigureKind::Wagon => {
+            return matches!(
+                feature_type,
+                Some(FeatureType::Road | FeatureType::Town | FeatureType::Cloister)
+            ) && meeple_can_be_placed(gameplay_data, tile_data, area_idx);
+        }
+        FigureKind::Abbot => {
+            return matches!(
+                tile_data.all_areas[area_idx].area_type,
+                AreaType::Cloister | AreaType::Garden
+            ) && !gameplay_data.area_meeples.contains_key(&area_idx)
+                && !area_is_under_dragon(gameplay_data, tile_data, area_idx);
+        }
+        FigureKind::Builder => vec![FeatureType::Road, FeatureType::Town],
+        FigureKind::Pig | FigureKind::Barn | FigureKind::Shepherd => vec![FeatureType::Farm],
+    };
+    if !feature_type.is_some_and(|f| allowed_feature_types.contains(&f))
+        || gameplay_data.area_meeples.contains_key(&area_idx)
+        || area_is_under_dragon(gameplay_data, tile_data, area_idx)
+    {
+        return false;
+    }
+    if matches!(
+        figure_kind(placed_meeple.meeple),
+        FigureKind::Barn | FigureKind::Shepherd
+    ) {
+        let kind = figure_kind(placed_meeple.meeple);
+        return !feature_has_figure(gameplay_data, area_idx, |m| figure_kind(m.meeple) == kind);
+    }
+    return feature_has_figure(gameplay_data, area_idx, |m| {
+        m.player == placed_meeple.player && figure_is_follower(m.meeple)
+    });
+}
+
+// Extending a road or town with your builder on it earns another turn, but never two in a
+// row. A builder put on the tile just placed doesn't count: it was not there to be extended.
+pub fn builder_earns_extra_turn(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    player: PlayerIndex,
+) -> bool {
+    if gameplay_data.in_extra_turn {
+        return false;
+    }
+    return tile_data.all_tiles[tile_idx].areas.iter().any(|a| {
+        matches!(
+            area_type_to_feature_type(tile_data.all_areas[*a].area_type),
+            Some(FeatureType::Road | FeatureType::Town)
+        ) && gameplay_data.get_connected_areas(*a).iter().any(|b| {
+            tile_data.all_areas[*b].tile_idx != tile_idx
+                && gameplay_data.area_meeples.get(b).is_some_and(|m| {
+                    m.player == player && figure_kind(m.meeple) == FigureKind::Builder
+                })
+        })
+    });
+}
+
+// Stands the meeple on the area and takes it out of its owner's supply. Whether it is
+// allowed there is up to the caller, see meeple_can_be_placed.
+pub fn place_meeple(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    area_idx: TileAreaIndex,
+    placed_meeple: PlacedMeeple,
+) {
+    gameplay_data.area_meeples.insert(area_idx, placed_meeple);
+    players.players[placed_meeple.player]
+        .meeples
+        .retain(|m| *m != placed_meeple.meeple);
+}
+
+// Takes every meeple off the feature and back into its owner's supply. Wagons get to move on
+// afterwards, see get_wagon_destinations.
+pub fn return_meeples_from_feature(
+    feature: &Feature,
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+) -> Vec<PlacedMeeple> {
+    let mut returned_meeples: Vec<PlacedMeeple> = vec![];
+    for area_idx in &feature.areas {
+        if let Some(placed_meeple) = remove_meeple(gameplay_data, players, *area_idx) {
+            if figure_kind(placed_meeple.meeple) == FigureKind::Wagon {
+                gameplay_data
+                    .pending_wagons
+                    .push((placed_meeple, *area_idx));
+            }
+            returned_meeples.push(placed_meeple);
+        }
+    }
+    return returned_meeples;
+}
+
+// Takes the figure on the area back into its owner's supply. The fairy stays behind, and a
+// shepherd's flock goes back in the bag.
+pub fn remove_meeple(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    area_idx: TileAreaIndex,
+) -> Option<PlacedMeeple> {
+    let placed_meeple = gameplay_data.area_meeples.remove(&area_idx)?;
+    players.players[placed_meeple.player]
+        .meeples
+        .push(placed_meeple.meeple);
+    if gameplay_data.fairy == Some(area_idx) {
+        gameplay_data.fairy = None;
+    }
+    if let Some(flock) = gameplay_data.flocks.remove(&area_idx) {
+        gameplay_data.sheep_bag.extend(flock);
+    }
+    return Some(placed_meeple);
+}
+
+// Meeples go on the tile just placed. A magic portal on it lets them go on any unfinished
+// road, town, cloister or field instead.
+pub fn meeple_area_is_reachable(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let Some(last_placed_tile) = gameplay_data.last_placed_tile else {
+        return false;
+    };
+    if tile_data.all_areas[area_idx].tile_idx == last_placed_tile {
+        return true;
+    }
+    if !tile_type_has_portal(tile_data.all_tiles[last_placed_tile].tile_type) {
+        return false;
+    }
+    return !area_feature_is_finished(gameplay_data, tile_data, area_idx);
+}
+
+fn area_feature_is_finished(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let area = &tile_data.all_areas[area_idx];
+    match area_type_to_feature_type(area.area_type) {
+        Some(FeatureType::Cloister) => {
+            let coords = gameplay_data.board_tile_matrix_inverse[&area.tile_idx];
+            return get_cloister_tiles(gameplay_data, coords).len() == SURROUNDING_COORDS.len() + 1;
+        }
+        // Fields are never finished.
+        Some(FeatureType::Farm) => false,
+        Some(feature_type) => {
+            let feature = get_feature(gameplay_data, tile_data, feature_type, area_idx);
+            return feature_is_closed(gameplay_data, tile_data, &feature);
+        }
+        None => true,
+    }
+}
+
+// Princess & Dragon
+
+// Steps the dragon takes each time a dragon tile is placed.
+pub const DRAGON_MOVES: usize = 6;
+
+// Sets the dragon off if the tile just placed has a dragon on it and the dragon is out.
+// Returns whether it did.
+pub fn start_dragon_move(gameplay_data: &mut GameplayData, tile_data: &GameTileData) -> bool {
+    let Some(tile_idx) = gameplay_data.last_placed_tile else {
+        return false;
+    };
+    let Some(dragon) = gameplay_data.dragon else {
+        return false;
+    };
+    if !tile_type_has_dragon(tile_data.all_tiles[tile_idx].tile_type) {
+        return false;
+    }
+    gameplay_data.dragon_path = vec![dragon];
+    gameplay_data.dragon_moves_left = DRAGON_MOVES;
+    if get_dragon_moves(gameplay_data, tile_data).is_empty() {
+        end_dragon_move(gameplay_data);
+        return false;
+    }
+    return true;
+}
+
+fn end_dragon_move(gameplay_data: &mut GameplayData) {
+    gameplay_data.dragon_path.clear();
+    gameplay_data.dragon_moves_left = 0;
+}
+
+// Players take turns moving the dragon, starting with the one who placed the dragon tile.
+pub fn get_dragon_mover(gameplay_data: &GameplayData, players: &Players) -> PlayerIndex {
+    let moves_made = DRAGON_MOVES - gameplay_data.dragon_moves_left;
+    return (players.current_player + moves_made) % players.players.len();
+}
+
+// Tiles next to the dragon it can step onto: not visited this move and not the fairy's.
+pub fn get_dragon_moves(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+) -> Vec<TileMatrixCoords> {
+    let Some(dragon) = gameplay_data.dragon else {
+        return vec![];
+    };
+    let fairy_coords = gameplay_data
+        .fairy
+        .map(|a| gameplay_data.board_tile_matrix_inverse[&tile_data.all_areas[a].tile_idx]);
+    return TILE_SIDES
+        .iter()
+        .map(|side| get_neighbor_coords(dragon, *side))
+        .filter(|c| gameplay_data.get_placed_tile(*c).is_some())
+        .filter(|c| !gameplay_data.dragon_path.contains(c))
+        .filter(|c| fairy_coords != Some(*c))
+        .collect();
+}
+
+// Moves the dragon one step and eats every meeple on the tile it lands on, except the one
+// the fairy protects. The move ends after DRAGON_MOVES steps or when the dragon is stuck.
+// Returns the eaten meeples, which go back to their owners.
+pub fn move_dragon(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+    coords: TileMatrixCoords,
+) -> Vec<PlacedMeeple> {
+    assert!(
+        get_dragon_moves(gameplay_data, tile_data).contains(&coords),
+        "dragon can't move to {:?}",
+        coords
+    );
+    gameplay_data.dragon = Some(coords);
+    gameplay_data.dragon_path.push(coords);
+    gameplay_data.dragon_moves_left -= 1;
+
+    let tile_idx = gameplay_data.get_placed_tile(coords).unwrap();
+    let mut eaten_meeples: Vec<PlacedMeeple> = vec![];
+    for area_idx in tile_data.all_tiles[tile_idx].areas.clone() {
+        if gameplay_data.fairy == Some(area_idx) {
+            continue;
+        }
+        if let Some(placed_meeple) = remove_meeple(gameplay_data, players, area_idx) {
+            eaten_meeples.push(placed_meeple);
+        }
+    }
+
+    if gameplay_data.dragon_moves_left == 0 || get_dragon_moves(gameplay_data, tile_data).is_empty()
+    {
+        end_dragon_move(gameplay_data);
+    }
+    return eaten_meeples;
+}
+
+// The fairy can join any of the player's meeples on the board, instead of placing one.
+pub fn fairy_can_be_placed(
+    gameplay_data: &GameplayData,
+    area_idx: TileAreaIndex,
+    player: PlayerIndex,
+) -> bool {
+    return gameplay_data
+        .area_meeples
+        .get(&area_idx)
+        .is_some_and(|m| m.player == player && figure_is_follower(m.meeple));
+}
+
+pub fn place_fairy(gameplay_data: &mut GameplayData, area_idx: TileAreaIndex) {
+    gameplay_data.fairy = Some(area_idx);
+}
+
+// Owner of the meeple the fairy is with, if it is with one.
+pub fn get_fairy_owner(gameplay_data: &GameplayData) -> Option<PlayerIndex> {
+    let area_idx = gameplay_data.fairy?;
+    return gameplay_data.area_meeples.get(&area_idx).map(|m| m.player);
+}
+
+// A player starting their turn with the fairy gets a point.
+pub fn score_fairy_turn_start(gameplay_data: &GameplayData, players: &mut Players) {
+    if get_fairy_owner(gameplay_data) == Some(players.current_player) {
+        let current_player = players.current_player;
+        players.players[current_player].points += 1;
+    }
+}
+
+// A princess on the tile just placed lets the player send home one knight from the town she
+// joined, instead of placing a meeple.
+pub fn princess_can_remove(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    area_idx: TileAreaIndex,
+) -> bool {
+    let Some(tile_idx) = gameplay_data.last_placed_tile else {
+        return false;
+    };
+    let has_knight = gameplay_data
+        .area_meeples
+        .get(&area_idx)
+        .is_some_and(|m| figure_is_follower(m.meeple));
+    if !has_knight
+        || !tile_type_has_princess(tile_data.all_tiles[tile_idx].tile_type)
+        || area_type_to_feature_type(tile_data.all_areas[area_idx].area_type)
+            != Some(FeatureType::Town)
+    {
+        return false;
+    }
+    let town_areas = gameplay_data.get_connected_areas(area_idx);
+    return tile_data.all_tiles[tile_idx]
+        .areas
+        .iter()
+        .any(|a| town_areas.contains(a));
+}
+
+// Abbey & Mayor
+
+// Hands the current player their abbey to play instead of drawing, if they still have it and
+// there is a hole for it.
+pub fn take_abbey(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+) -> Option<TileIndex> {
+    let current_player = players.current_player;
+    if !players.players[current_player].has_abbey {
+        return None;
+    }
+    let abbey = *gameplay_data.abbey_tiles.last()?;
+    if get_legal_placements(gameplay_data, tile_data, abbey).is_empty() {
+        return None;
+    }
+    players.players[current_player].has_abbey = false;
+    return gameplay_data.abbey_tiles.pop();
+}
+
+// Unfinished and unclaimed roads, towns and cloisters on the wagon's tile or the tiles next to
+// it, where a wagon from a completed feature can go.
+pub fn get_wagon_destinations(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    from_area: TileAreaIndex,
+) -> Vec<TileAreaIndex> {
+    let from_coords =
+        gameplay_data.board_tile_matrix_inverse[&tile_data.all_areas[from_area].tile_idx];
+    let mut tiles: Vec<TileIndex> = vec![gameplay_data.board_tile_matrix[&from_coords]];
+    for side in TILE_SIDES {
+        if let Some(tile_idx) =
+            gameplay_data.get_placed_tile(get_neighbor_coords(from_coords, side))
+        {
+            tiles.push(tile_idx);
+        }
+    }
+    let mut destinations: Vec<TileAreaIndex> = vec![];
+    for tile_idx in tiles {
+        for area_idx in &tile_data.all_tiles[tile_idx].areas {
+            if matches!(
+                area_type_to_feature_type(tile_data.all_areas[*area_idx].area_type),
+                Some(FeatureType::Road | FeatureType::Town | FeatureType::Cloister)
+            ) && meeple_can_be_placed(gameplay_data, tile_data, *area_idx)
+                && !area_feature_is_finished(gameplay_data, tile_data, *area_idx)
+            {
+                destinations.push(*area_idx);
+            }
+        }
+    }
+    return destinations;
+}
+
+// Forgets the wagons that have nowhere to go. They just stay in their owners' supply.
+pub fn keep_movable_wagons(gameplay_data: &mut GameplayData, tile_data: &GameTileData) {
+    let pending_wagons = std::mem::take(&mut gameplay_data.pending_wagons);
+    gameplay_data.pending_wagons = pending_wagons
+        .into_iter()
+        .filter(|(_, from_area)| {
+            !get_wagon_destinations(gameplay_data, tile_data, *from_area).is_empty()
+        })
+        .collect();
+}
+
+// Ransom paid to get a captured meeple back.
+pub const RANSOM_POINTS: i32 = 3;
+
+// A tower piece can go on any tower foundation on the board, however tall the tower is.
+pub fn tower_can_be_built(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+    coords: TileMatrixCoords,
+) -> bool {
+    let Some(tile_idx) = gameplay_data.get_placed_tile(coords) else {
+        return false;
+    };
+    return tile_type_has_tower_foundation(tile_data.all_tiles[tile_idx].tile_type)
+        && players.current().tower_pieces > 0;
+}
+
+// Stacks one of the current player's pieces on the tower at coords, instead of placing a
+// meeple. Returns the new height.
+pub fn build_tower(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+    coords: TileMatrixCoords,
+) -> Option<usize> {
+    if !tower_can_be_built(gameplay_data, tile_data, players, coords) {
+        return None;
+    }
+    let current_player = players.current_player;
+    players.players[current_player].tower_pieces -= 1;
+    let height = gameplay_data.towers.entry(coords).or_insert(0);
+    *height += 1;
+    let height = *height;
+    gameplay_data.tower_built = Some(coords);
+    return Some(height);
+}
+
+// Areas with a follower the tower at coords can see: on the tower's own tile and on the
+// tiles straight out from it in each direction, as far as the tower is tall.
+pub fn get_tower_captures(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    coords: TileMatrixCoords,
+) -> Vec<TileAreaIndex> {
+    let height = gameplay_data.towers.get(&coords).copied().unwrap_or(0) as i32;
+    let mut seen_coords: Vec<TileMatrixCoords> = vec![coords];
+    for offset in NEIGHBOR_COORDS {
+        for distance in 1..=height {
+            seen_coords.push(TileMatrixCoords {
+                x: coords.x + offset.x * distance,
+                y: coords.y + offset.y * distance,
+            });
+        }
+    }
+    let mut captures: Vec<TileAreaIndex> = vec![];
+    for seen in seen_coords {
+        let Some(tile_idx) = gameplay_data.get_placed_tile(seen) else {
+            continue;
+        };
+        for area_idx in &tile_data.all_tiles[tile_idx].areas {
+            let has_follower = gameplay_data
+                .area_meeples
+                .get(area_idx)
+                .is_some_and(|m| figure_is_follower(m.meeple));
+            if has_follower {
+                captures.push(*area_idx);
+            }
+        }
+    }
+    return captures;
+}
+
+// Takes the meeple on the area off the board. The current player keeps other players'
+// meeples as prisoners, their own goes straight home. Returns the captured meeple and any
+// prisoners that went home because of it.
+pub fn capture_meeple(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    area_idx: TileAreaIndex,
+) -> (Option<PlacedMeeple>, Vec<PlacedMeeple>) {
+    let Some(captured) = remove_meeple(gameplay_data, players, area_idx) else {
+        return (None, vec![]);
+    };
+    let current_player = players.current_player;
+    if captured.player == current_player {
+        return (Some(captured), vec![]);
+    }
+    players.players[captured.player]
+        .meeples
+        .retain(|m| *m != captured.meeple);
+    players.players[current_player].prisoners.push(captured);
+    let released = exchange_prisoners(players, current_player, captured.player);
+    return (Some(captured), released);
+}
+
+// Two players holding each other's meeples swap them back, one for one.
+pub fn exchange_prisoners(
+    players: &mut Players,
+    a: PlayerIndex,
+    b: PlayerIndex,
+) -> Vec<PlacedMeeple> {
+    let Some(a_held) = players.players[a]
+        .prisoners
+        .iter()
+        .position(|m| m.player == b)
+    else {
+        return vec![];
+    };
+    let Some(b_held) = players.players[b]
+        .prisoners
+        .iter()
+        .position(|m| m.player == a)
+    else {
+        return vec![];
+    };
+    let from_a = players.players[a].prisoners.remove(a_held);
+    let from_b = players.players[b].prisoners.remove(b_held);
+    players.players[b].meeples.push(from_a.meeple);
+    players.players[a].meeples.push(from_b.meeple);
+    return vec![from_a, from_b];
+}
+
+// The current player pays RANSOM_POINTS to whoever holds one of their meeples to get it
+// back.
+pub fn pay_ransom(players: &mut Players) -> Option<PlacedMeeple> {
+    let current_player = players.current_player;
+    if players.players[current_player].points < RANSOM_POINTS {
+        return None;
+    }
+    let (holder, prisoner_idx) = players.players.iter().enumerate().find_map(|(p, player)| {
+        player
+            .prisoners
+            .iter()
+            .position(|m| m.player == current_player)
+            .map(|i| (p, i))
+    })?;
+    let prisoner = players.players[holder].prisoners.remove(prisoner_idx);
+    players.players[holder].points += RANSOM_POINTS;
+    players.players[current_player].points -= RANSOM_POINTS;
+    players.players[current_player]
+        .meeples
+        .push(prisoner.meeple);
+    return Some(prisoner);
+}
+
+// Takes a random token out of the sheep bag. Uses the game's rng so seeded games replay.
+pub fn draw_sheep_token(gameplay_data: &mut GameplayData) -> Option<SheepToken> {
+    if gameplay_data.sheep_bag.is_empty() {
+        return None;
+    }
+    let token_idx = gameplay_data
+        .rng
+        .gen_range(0..gameplay_data.sheep_bag.len());
+    return Some(gameplay_data.sheep_bag.remove(token_idx));
+}
+
+// Shepherd areas in the field the area belongs to.
+fn get_field_shepherds(
+    gameplay_data: &GameplayData,
+    area_idx: TileAreaIndex,
+) -> Vec<TileAreaIndex> {
+    let mut shepherds: Vec<TileAreaIndex> = gameplay_data
+        .get_connected_areas(area_idx)
+        .into_iter()
+        .filter(|a| {
+            gameplay_data
+                .area_meeples
+                .get(a)
+                .is_some_and(|m| figure_kind(m.meeple) == FigureKind::Shepherd)
+        })
+        .collect();
+    shepherds.sort();
+    return shepherds;
+}
+
+// The shepherd draws a token for its flock. A wolf eats every flock in the field: the sheep
+// go back in the bag and the shepherds go home. Returns the token and the shepherds sent
+// home.
+pub fn grow_flock(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    shepherd_area: TileAreaIndex,
+) -> (Option<SheepToken>, Vec<PlacedMeeple>) {
+    let Some(token) = draw_sheep_token(gameplay_data) else {
+        return (None, vec![]);
+    };
+    if token != SheepToken::Wolf {
+        gameplay_data
+            .flocks
+            .entry(shepherd_area)
+            .or_default()
+            .push(token);
+        return (Some(token), vec![]);
+    }
+    gameplay_data.sheep_bag.push(token);
+    let mut returned_meeples: Vec<PlacedMeeple> = vec![];
+    for area_idx in get_field_shepherds(gameplay_data, shepherd_area) {
+        returned_meeples.extend(remove_meeple(gameplay_data, players, area_idx));
+    }
+    return (Some(token), returned_meeples);
+}
+
+// Every shepherd in the field scores a point for each sheep in all of the field's flocks.
+// The sheep go back in the bag and the shepherds go home.
+pub fn drive_flock_home(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    shepherd_area: TileAreaIndex,
+) -> Vec<PlacedMeeple> {
+    let shepherds = get_field_shepherds(gameplay_data, shepherd_area);
+    let mut num_sheep = 0;
+    for area_idx in &shepherds {
+        let flock = gameplay_data.flocks.remove(area_idx).unwrap_or_default();
+        for token in &flock {
+            if let SheepToken::Sheep(n) = token {
+                num_sheep += n;
+            }
+        }
+        gameplay_data.sheep_bag.extend(flock);
+    }
+    let mut returned_meeples: Vec<PlacedMeeple> = vec![];
+    for area_idx in shepherds {
+        if let Some(shepherd) = remove_meeple(gameplay_data, players, area_idx) {
+            players.players[shepherd.player].points += num_sheep as i32;
+            returned_meeples.push(shepherd);
+        }
+    }
+    return returned_meeples;
+}
+
+// Area of the player's shepherd, if the tile just extended its field. A shepherd placed on
+// the tile itself already drew its first token.
+pub fn get_flock_to_tend(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    tile_idx: TileIndex,
+    player: PlayerIndex,
+) -> Option<TileAreaIndex> {
+    for area_idx in &tile_data.all_tiles[tile_idx].areas {
+        if tile_data.all_areas[*area_idx].area_type != AreaType::Farm {
+            continue;
+        }
+        for shepherd_area in get_field_shepherds(gameplay_data, *area_idx) {
+            if gameplay_data.area_meeples[&shepherd_area].player == player
+                && tile_data.all_areas[shepherd_area].tile_idx != tile_idx
+            {
+                return Some(shepherd_area);
+            }
+        }
+    }
+    return None;
+}
+
+// A bridge carries a road over the middle of a field on the tile, so a tile can go where its
+// road would otherwise run into a field, or a field into a road. Returns the way the bridge
+// would run, if the current player has one left and it makes the tile fit.
+pub fn get_bridge_for_fit(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+    tile_idx: TileIndex,
+    dir: TileDirection,
+    coords: TileMatrixCoords,
+) -> Option<BridgeAxis> {
+    let tile_type = tile_data.all_tiles[tile_idx].tile_type;
+    if players.current().bridges == 0
+        || tile_type_is_river(tile_type)
+        || tile_type_is_abbey(tile_type)
+    {
+        return None;
+    }
+    let edge_areas = get_rotated_edge_areas(&tile_data.all_tiles[tile_idx], dir, tile_data);
+    for axis in BRIDGE_AXES {
+        let ends = bridge_edges(axis);
+        if ends
+            .iter()
+            .any(|e| tile_data.all_areas[edge_areas[*e]].area_type != AreaType::Farm)
+        {
+            continue;
+        }
+        let mut edge_types = edge_areas.map(|a| tile_data.all_areas[a].area_type);
+        for edge in ends {
+            edge_types[edge] = AreaType::Road;
+        }
+        if edges_fit_at(gameplay_data, tile_data, edge_types, coords) {
+            return Some(axis);
+        }
+    }
+    return None;
+}
+
+// Lays one of the current player's bridges across a placed tile and joins it to the roads at
+// its ends. Returns the bridge's road area.
+pub fn place_bridge(
+    gameplay_data: &mut GameplayData,
+    tile_data: &mut GameTileData,
+    players: &mut Players,
+    tile_idx: TileIndex,
+    axis: BridgeAxis,
+) -> TileAreaIndex {
+    let dir = gameplay_data.board_tile_directions[&tile_idx];
+    let coords = gameplay_data.board_tile_matrix_inverse[&tile_idx];
+    let edges: Vec<EdgeNumber> = bridge_edges(axis)
+        .iter()
+        .map(|e| unrotate_edge(*e, dir))
+        .collect();
+    let area_idx = add_bridge_area(tile_data, tile_idx, edges);
+    let node = gameplay_data.board_area_graph.add_node(area_idx);
+    gameplay_data
+        .area_index_to_area_graph_index
+        .insert(area_idx, node);
+    for edge in bridge_edges(axis) {
+        let Some(neighbor_idx) =
+            gameplay_data.get_placed_tile(get_neighbor_coords(coords, edge_side(edge)))
+        else {
+            continue;
+        };
+        let neighbor_edge_areas = get_rotated_edge_areas(
+            &tile_data.all_tiles[neighbor_idx],
+            gameplay_data.board_tile_directions[&neighbor_idx],
+            tile_data,
+        );
+        let neighbor_area_idx = neighbor_edge_areas[opposite_edge(edge)];
+        if area_types_connect(
+            AreaType::Road,
+            tile_data.all_areas[neighbor_area_idx].area_type,
+        ) {
+            let neighbor_node = gameplay_data.area_index_to_area_graph_index[&neighbor_area_idx];
+            gameplay_data
+                .board_area_graph
+                .update_edge(node, neighbor_node, ());
+        }
+    }
+    let current_player = players.current_player;
+    players.players[current_player].bridges -= 1;
+    return area_idx;
+}
+
+// A completed town of just two tiles with a single meeple in it may become a castle instead
+// of scoring, if its owner wants.
+pub fn feature_can_become_castle(gameplay_data: &GameplayData, feature: &Feature) -> bool {
+    if feature.feature_type != FeatureType::Town || feature.tiles.len() != 2 {
+        return false;
+    }
+    let figures: Vec<&PlacedMeeple> = feature
+        .areas
+        .iter()
+        .filter_map(|a| gameplay_data.area_meeples.get(a))
+        .collect();
+    return figures.len() == 1 && figure_is_follower(figures[0].meeple);
+}
+
+// The town's meeple moves onto the castle, off the board's areas.
+pub fn build_castle(gameplay_data: &mut GameplayData, feature: &Feature) {
+    let Some(&area) = feature
+        .areas
+        .iter()
+        .find(|a| gameplay_data.area_meeples.contains_key(a))
+    else {
+        return;
+    };
+    let meeple = gameplay_data.area_meeples.remove(&area).unwrap();
+    if gameplay_data.fairy == Some(area) {
+        gameplay_data.fairy = None;
+    }
+    gameplay_data.castles.push(Castle {
+        tiles: feature.tiles.clone(),
+        area,
+        meeple,
+    });
+}
+
+// The castle's fief: its tiles and every tile around them, diagonals included.
+pub fn get_castle_surroundings(gameplay_data: &GameplayData, castle: &Castle) -> Vec<TileIndex> {
+    let mut tiles: Vec<TileIndex> = castle.tiles.clone();
+    for tile_idx in &castle.tiles {
+        let coords = gameplay_data.board_tile_matrix_inverse[tile_idx];
+        for offset in SURROUNDING_COORDS {
+            let neighbor_coords = TileMatrixCoords {
+                x: coords.x + offset.x,
+                y: coords.y + offset.y,
+            };
+            if let Some(neighbor_idx) = gameplay_data.get_placed_tile(neighbor_coords) {
+                if !tiles.contains(&neighbor_idx) {
+                    tiles.push(neighbor_idx);
+                }
+            }
+        }
+    }
+    return tiles;
+}
+
+// Puts a tile per player from the bag in the market. Whether anyone can place them is only
+// checked once they are played. Everyone gets to buy one, starting with the player after the
+// one who placed the bazaar. Nothing happens if the bag is too low.
+pub fn open_bazaar(gameplay_data: &mut GameplayData, players: &mut Players) -> bool {
+    gameplay_data.bazaar_pending = false;
+    let num_players = players.players.len();
+    if gameplay_data.unspawned_tiles.len() < num_players {
+        return false;
+    }
+    for _ in 0..num_players {
+        if let Some(tile_idx) = take_random_bag_tile(gameplay_data) {
+            gameplay_data.market.push(tile_idx);
+        }
+    }
+    gameplay_data.bazaar_players = (1..=num_players)
+        .map(|i| (players.current_player + i) % num_players)
+        .collect();
+    start_auction(gameplay_data, players);
+    return gameplay_data.auction.is_some();
+}
+
+// The next player without a tile puts the first market tile up for auction. The last one left
+// gets the last tile for free. Closes the bazaar once the market is empty.
+pub fn start_auction(gameplay_data: &mut GameplayData, players: &mut Players) {
+    while !gameplay_data.market.is_empty() && !gameplay_data.bazaar_players.is_empty() {
+        let auctioneer = gameplay_data.bazaar_players[0];
+        let tile = gameplay_data.market[0];
+        if gameplay_data.market.len() > 1 && gameplay_data.bazaar_players.len() > 1 {
+            gameplay_data.auction = Some(Auction {
+                tile,
+                auctioneer,
+                high_bid: 0,
+                high_bidder: auctioneer,
+                bidders: gameplay_data.bazaar_players[1..].to_vec(),
+            });
+            return;
+        }
+        players.players[auctioneer].bought_tiles.push(tile);
+        gameplay_data.market.remove(0);
+        gameplay_data.bazaar_players.remove(0);
+    }
+    // Tiles nobody could take go back in the bag.
+    let mut market = std::mem::take(&mut gameplay_data.market);
+    gameplay_data.unspawned_tiles.append(&mut market);
+    gameplay_data.bazaar_players.clear();
+    gameplay_data.auction = None;
+}
+
+// The next bidder bids, as long as they can pay it. Anything not above the high bid passes.
+pub fn bid_in_auction(gameplay_data: &mut GameplayData, players: &Players, bid: i32) {
+    let Some(auction) = gameplay_data.auction.as_mut() else {
+        return;
+    };
+    if auction.bidders.is_empty() {
+        return;
+    }
+    let bidder = auction.bidders.remove(0);
+    if bid > auction.high_bid && players.players[bidder].points >= bid {
+        auction.high_bid = bid;
+        auction.high_bidder = bidder;
+    }
+}
+
+// Once everyone has bid, the auctioneer either buys the tile by paying the high bid to the
+// high bidder, or sells it to them for that much. Nobody outbidding the auctioneer gets
+// them the tile for free. Then the next auction starts. An auctioneer without the points to
+// buy has to sell: returns false and leaves the auction open.
+pub fn close_auction(
+    gameplay_data: &mut GameplayData,
+    players: &mut Players,
+    auctioneer_buys: bool,
+) -> bool {
+    let Some(auction) = gameplay_data.auction.clone() else {
+        return false;
+    };
+    if auctioneer_buys
+        && auction.high_bidder != auction.auctioneer
+        && players.players[auction.auctioneer].points < auction.high_bid
+    {
+        return false;
+    }
+    gameplay_data.auction = None;
+    let buyer = if auctioneer_buys {
+        auction.auctioneer
+    } else {
+        auction.high_bidder
+    };
+    if auction.high_bidder != auction.auctioneer {
+        let seller = if buyer == auction.auctioneer {
+            auction.high_bidder
+        } else {
+            auction.auctioneer
+        };
+        players.players[buyer].points -= auction.high_bid;
+        players.players[seller].points += auction.high_bid;
+    }
+    players.players[buyer].bought_tiles.push(auction.tile);
+    gameplay_data.market.retain(|t| *t != auction.tile);
+    gameplay_data.bazaar_players.retain(|p| *p != buyer);
+    start_auction(gameplay_data, players);
+    return true;
+}
+
+// A tile the current player bought at a bazaar, played instead of drawing one. Bought tiles
+// that fit nowhere any more are discarded.
+pub fn take_bought_tile(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+) -> Option<TileIndex> {
+    let current_player = players.current_player;
+    while let Some(tile_idx) = players.players[current_player].bought_tiles.pop() {
+        if !tile_can_be_placed(gameplay_data, tile_data, players, tile_idx) {
+            gameplay_data.discarded_tiles.push(tile_idx);
+            continue;
+        }
+        gameplay_data.playing_bought_tile = true;
+        return Some(tile_idx);
+    }
+    return None;
+}
+
+// Gardens
+
+// Instead of placing a figure, a player can take their abbot back, scoring its cloister or
+// garden as it stands: a point for it and each tile around it. Returns the abbot and the
+// points.
+pub fn recall_abbot(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+) -> Option<(PlacedMeeple, i32)> {
+    let abbot = abbot_index(players.current_player);
+    let area_idx = *gameplay_data
+        .area_meeples
+        .iter()
+        .find(|(_, m)| m.meeple == abbot)?
+        .0;
+    let coords = gameplay_data.board_tile_matrix_inverse[&tile_data.all_areas[area_idx].tile_idx];
+    let points = get_cloister_tiles(gameplay_data, coords).len() as i32;
+    let placed_meeple = remove_meeple(gameplay_data, players, area_idx)?;
+    players.players[placed_meeple.player].points += points;
+    return Some((placed_meeple, points));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nth_tile(tile_data: &GameTileData, tile_type: TileType, n: usize) -> TileIndex {
+        return tile_data
+            .all_tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.tile_type == tile_type)
+            .nth(n)
+            .unwrap()
+            .0;
+    }
+
+    fn area_of(
+        tile_data: &GameTileData,
+        tile_idx: TileIndex,
+        area_type: AreaType,
+    ) -> TileAreaIndex {
+        return *tile_data.all_tiles[tile_idx]
+            .areas
+            .iter()
+            .find(|a| tile_data.all_areas[**a].area_type == area_type)
+            .unwrap();
+    }
+
+    fn place_at(
+        gameplay_data: &mut GameplayData,
+        tile_data: &GameTileData,
+        tile_idx: TileIndex,
+        dir: TileDirection,
+        x: i32,
+        y: i32,
+    ) {
+        let coords = TileMatrixCoords { x, y };
+        assert!(tile_fits_at(
+            gameplay_data,
+            tile_data,
+            tile_idx,
+            dir,
+            coords
+        ));
+        place_tile(gameplay_data, tile_data, tile_idx, dir, coords);
+    }
+
+    #[test]
+    fn tile_fits_at_matches_every_side() {
+        let tile_data = create_tiles(&[]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        // The road runs east to west, so the town can't face it from the north.
+        let town = nth_tile(&tile_data, TileType::FFFT, 0);
+        let north = TileMatrixCoords { x: 0, y: 1 };
+        assert!(tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            town,
+            TileDirection::UP,
+            north
+        ));
+        assert!(!tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            town,
+            TileDirection::DOWN,
+            north
+        ));
+        // A straight road only fits next to a road end once it is turned along it.
+        let road2 = nth_tile(&tile_data, TileType::RFRF_02, 1);
+        let east = TileMatrixCoords { x: 1, y: 0 };
+        assert!(tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            road2,
+            TileDirection::UP,
+            east
+        ));
+        assert!(!tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            road2,
+            TileDirection::RIGHT,
+            east
+        ));
+    }
+
+    #[test]
+    fn legal_placements_cover_the_frontier() {
+        let tile_data = create_tiles(&[]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        assert_eq!(
+            get_legal_placements(&gameplay_data, &tile_data, road).len(),
+            4
+        );
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        // North and south take the town turned any way but towards the field, east and west
+        // would need a road.
+        let town = nth_tile(&tile_data, TileType::FFFT, 0);
+        let placements = get_legal_placements(&gameplay_data, &tile_data, town);
+        assert_eq!(placements.len(), 6);
+        assert!(placements.iter().all(|(coords, _)| coords.x == 0));
+    }
+
+    #[test]
+    fn road_between_end_roads_completes() {
+        let tile_data = create_tiles(&[]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let crossing = nth_tile(&tile_data, TileType::RRRF, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            crossing,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        assert!(find_completed_features(&gameplay_data, &tile_data, crossing).is_empty());
+        let crossing2 = nth_tile(&tile_data, TileType::RRRF, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            crossing2,
+            TileDirection::UP,
+            -1,
+            0,
+        );
+        let completed = find_completed_features(&gameplay_data, &tile_data, crossing2);
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].feature_type, FeatureType::Road);
+        assert_eq!(completed[0].tiles.len(), 3);
+    }
+
+    #[test]
+    fn road_loop_completes() {
+        let tile_data = create_tiles(&[]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let placements = [
+            (TileDirection::DOWN, 0, 0),
+            (TileDirection::LEFT, 1, 0),
+            (TileDirection::UP, 1, 1),
+            (TileDirection::RIGHT, 0, 1),
+        ];
+        let mut completed = vec![];
+        for (n, (dir, x, y)) in placements.into_iter().enumerate() {
+            let curve = nth_tile(&tile_data, TileType::FRRF_12, n);
+            place_at(&mut gameplay_data, &tile_data, curve, dir, x, y);
+            completed = find_completed_features(&gameplay_data, &tile_data, curve);
+            if n < 3 {
+                assert!(completed.is_empty());
+            }
+        }
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].feature_type, FeatureType::Road);
+        assert_eq!(completed[0].tiles.len(), 4);
+    }
+
+    #[test]
+    fn cloister_completes_once_surrounded() {
+        let tile_data = create_tiles(&[]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let cloister = nth_tile(&tile_data, TileType::FFFF_C, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            cloister,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        // Other cloisters to the sides, towns facing away above and below.
+        let mut neighbors = vec![
+            (
+                nth_tile(&tile_data, TileType::FFFF_C, 1),
+                TileDirection::UP,
+                -1,
+                0,
+            ),
+            (
+                nth_tile(&tile_data, TileType::FFFF_C, 2),
+                TileDirection::UP,
+                1,
+                0,
+            ),
+            (
+                nth_tile(&tile_data, TileType::FFFF_C, 3),
+                TileDirection::UP,
+                0,
+                -1,
+            ),
+        ];
+        for (n, (x, y)) in [(-1, 1), (0, 1), (1, 1)].into_iter().enumerate() {
+            neighbors.push((
+                nth_tile(&tile_data, TileType::FFFT, n),
+                TileDirection::UP,
+                x,
+                y,
+            ));
+        }
+        for (n, (x, y)) in [(-1, -1), (1, -1)].into_iter().enumerate() {
+            neighbors.push((
+                nth_tile(&tile_data, TileType::FFFT, 3 + n),
+                TileDirection::DOWN,
+                x,
+                y,
+            ));
+        }
+        let last = neighbors.len() - 1;
+        for (n, (tile_idx, dir, x, y)) in neighbors.into_iter().enumerate() {
+            place_at(&mut gameplay_data, &tile_data, tile_idx, dir, x, y);
+            let completed = find_completed_features(&gameplay_data, &tile_data, tile_idx);
+            if n < last {
+                assert!(completed.is_empty());
+                continue;
+            }
+            assert_eq!(completed.len(), 1);
+            assert_eq!(completed[0].feature_type, FeatureType::Cloister);
+            assert_eq!(completed[0].tiles.len(), 9);
+        }
+        let origin = TileMatrixCoords { x: 0, y: 0 };
+        assert_eq!(count_cloister_neighbors(&gameplay_data, origin), 8);
+    }
+
+    #[test]
+    fn river_tile_that_does_not_fit_stays_in_the_stack() {
+        let tile_data = create_tiles(&[Expansion::River]);
+        let players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        for seed in 0..20 {
+            let mut gameplay_data = GameplayData::new(&tile_data, seed);
+            place_start_tile(&mut gameplay_data, &tile_data);
+            // A road east of the spot below the spring only lets the river through if a road
+            // crosses it there.
+            let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+            let east = TileMatrixCoords { x: 1, y: -1 };
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                road,
+                TileDirection::UP,
+                east,
+            );
+            let straight = nth_tile(&tile_data, TileType::WFWF_02, 0);
+            let crossing = nth_tile(&tile_data, TileType::WRWR_02_13, 0);
+            gameplay_data.river_tiles = vec![straight, crossing];
+
+            let drawn = draw_tile(&mut gameplay_data, &tile_data, &players);
+            assert_eq!(drawn, Some(crossing));
+            assert_eq!(gameplay_data.river_tiles, vec![straight]);
+            assert!(gameplay_data.discarded_tiles.is_empty());
+        }
+    }
+
+    #[test]
+    fn river_never_bends_the_same_way_twice() {
+        let tile_data = create_tiles(&[Expansion::River]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 1);
+        place_start_tile(&mut gameplay_data, &tile_data);
+        // Only just below the spring, and the first bend can go either way.
+        let curve = nth_tile(&tile_data, TileType::FWWF_12, 0);
+        let placements = get_legal_placements(&gameplay_data, &tile_data, curve);
+        assert_eq!(placements.len(), 2);
+        let below = TileMatrixCoords { x: 0, y: -1 };
+        assert!(placements.iter().all(|(coords, _)| *coords == below));
+        let (coords, dir) = placements[0];
+        place_tile(&mut gameplay_data, &tile_data, curve, dir, coords);
+        let first_turn = gameplay_data.last_river_turn.unwrap();
+
+        let curve2 = nth_tile(&tile_data, TileType::FWWF_12, 1);
+        let placements = get_legal_placements(&gameplay_data, &tile_data, curve2);
+        assert_eq!(placements.len(), 1);
+        let (coords, dir) = placements[0];
+        place_tile(&mut gameplay_data, &tile_data, curve2, dir, coords);
+        assert_ne!(gameplay_data.last_river_turn, Some(first_turn));
+    }
+
+    #[test]
+    fn lake_closes_the_river_last() {
+        let tile_data = create_tiles(&[Expansion::River]);
+        let players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        for seed in 0..20 {
+            let mut gameplay_data = GameplayData::new(&tile_data, seed);
+            place_start_tile(&mut gameplay_data, &tile_data);
+            while !gameplay_data.river_tiles.is_empty() {
+                let tile_idx = draw_tile(&mut gameplay_data, &tile_data, &players).unwrap();
+                let tile_type = tile_data.all_tiles[tile_idx].tile_type;
+                assert!(tile_type_is_river(tile_type));
+                assert_eq!(
+                    tile_type == RIVER_END_TILE_TYPE,
+                    gameplay_data.river_tiles.is_empty()
+                );
+                let (coords, dir) = get_legal_placements(&gameplay_data, &tile_data, tile_idx)[0];
+                place_tile(&mut gameplay_data, &tile_data, tile_idx, dir, coords);
+            }
+            assert_eq!(gameplay_data.river_end, None);
+            assert!(gameplay_data.discarded_tiles.is_empty());
+            let tile_idx = draw_tile(&mut gameplay_data, &tile_data, &players).unwrap();
+            assert!(!tile_type_is_river(tile_data.all_tiles[tile_idx].tile_type));
+        }
+    }
+
+    #[test]
+    fn seeded_draws_repeat() {
+        let tile_data = create_tiles(&[]);
+        let players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        let draws = |seed| {
+            let mut gameplay_data = GameplayData::new(&tile_data, seed);
+            return (0..10)
+                .map(|_| draw_tile(&mut gameplay_data, &tile_data, &players).unwrap())
+                .collect::<Vec<_>>();
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+    }
+
+    #[test]
+    fn builder_earns_one_extra_turn() {
+        let tile_data = create_tiles(&[Expansion::TradersAndBuilders]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::TradersAndBuilders],
+        );
+        let builder = PlacedMeeple {
+            meeple: builder_index(0),
+            player: 0,
+        };
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        // A builder needs its owner's meeple on the road first.
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            road_area,
+            builder
+        ));
+        let meeple = PlacedMeeple {
+            meeple: 0,
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, road_area, meeple);
+
+        let road2 = nth_tile(&tile_data, TileType::RFRF_02, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road2,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        let road2_area = area_of(&tile_data, road2, AreaType::Road);
+        let other_builder = PlacedMeeple {
+            meeple: builder_index(1),
+            player: 1,
+        };
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            road2_area,
+            other_builder
+        ));
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            road2_area,
+            builder
+        ));
+        place_meeple(&mut gameplay_data, &mut players, road2_area, builder);
+        // It was only just put down.
+        assert!(!builder_earns_extra_turn(
+            &gameplay_data,
+            &tile_data,
+            road2,
+            0
+        ));
+
+        let road3 = nth_tile(&tile_data, TileType::RFRF_02, 2);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road3,
+            TileDirection::UP,
+            2,
+            0,
+        );
+        assert!(builder_earns_extra_turn(
+            &gameplay_data,
+            &tile_data,
+            road3,
+            0
+        ));
+        assert!(!builder_earns_extra_turn(
+            &gameplay_data,
+            &tile_data,
+            road3,
+            1
+        ));
+        gameplay_data.in_extra_turn = true;
+        assert!(!builder_earns_extra_turn(
+            &gameplay_data,
+            &tile_data,
+            road3,
+            0
+        ));
+    }
+
+    #[test]
+    fn dragon_tiles_wait_for_the_volcano() {
+        let tile_data = create_tiles(&[Expansion::PrincessAndDragon]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        while let Some(tile_idx) = draw_tile(&mut gameplay_data, &tile_data, &players) {
+            assert!(!tile_type_has_dragon(
+                tile_data.all_tiles[tile_idx].tile_type
+            ));
+        }
+        assert_eq!(gameplay_data.set_aside_tiles.len(), 4);
+
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let dragon_tile = nth_tile(&tile_data, TileType::FTFT_Dragon, 0);
+        gameplay_data.set_aside_tiles.push(dragon_tile);
+        let volcano = nth_tile(&tile_data, TileType::FFFF_Volcano, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            volcano,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        assert_eq!(gameplay_data.dragon, Some(TileMatrixCoords { x: 0, y: 0 }));
+        assert!(gameplay_data.set_aside_tiles.is_empty());
+        assert!(gameplay_data.unspawned_tiles.contains(&dragon_tile));
+        let volcano_area = tile_data.all_tiles[volcano].areas[0];
+        assert!(!meeple_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            volcano_area
+        ));
+    }
+
+    #[test]
+    fn dragon_eats_meeples_but_not_the_fairys() {
+        let tile_data = create_tiles(&[Expansion::PrincessAndDragon]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        let meeple = PlacedMeeple {
+            meeple: 0,
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, road_area, meeple);
+        let road2 = nth_tile(&tile_data, TileType::RFRF_02, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road2,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        let farm_area = area_of(&tile_data, road2, AreaType::Farm);
+        let farmer = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, farm_area, farmer);
+
+        // The fairy only joins the player's own meeples, and pays a point a turn.
+        assert!(!fairy_can_be_placed(&gameplay_data, road_area, 1));
+        assert!(!fairy_can_be_placed(&gameplay_data, farm_area, 0));
+        assert!(fairy_can_be_placed(&gameplay_data, road_area, 0));
+        place_fairy(&mut gameplay_data, road_area);
+        score_fairy_turn_start(&gameplay_data, &mut players);
+        assert_eq!(players.players[0].points, 1);
+
+        let volcano = nth_tile(&tile_data, TileType::FFFF_Volcano, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            volcano,
+            TileDirection::UP,
+            1,
+            1,
+        );
+        // Nothing happens until a dragon tile comes out.
+        assert!(!start_dragon_move(&mut gameplay_data, &tile_data));
+        let dragon_tile = nth_tile(&tile_data, TileType::RFRF_02_Dragon, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            dragon_tile,
+            TileDirection::UP,
+            2,
+            0,
+        );
+        assert!(start_dragon_move(&mut gameplay_data, &tile_data));
+        assert_eq!(get_dragon_mover(&gameplay_data, &players), 0);
+        let below = TileMatrixCoords { x: 1, y: 0 };
+        assert_eq!(get_dragon_moves(&gameplay_data, &tile_data), vec![below]);
+        let eaten = move_dragon(&mut gameplay_data, &tile_data, &mut players, below);
+        assert_eq!(eaten.len(), 1);
+        assert_eq!(eaten[0].player, 1);
+        assert_eq!(players.players[1].meeples.len(), NUM_MEEPLES);
+        assert_eq!(get_dragon_mover(&gameplay_data, &players), 1);
+
+        // The fairy's tile is off limits, and once stuck the dragon stops.
+        let east = TileMatrixCoords { x: 2, y: 0 };
+        assert_eq!(get_dragon_moves(&gameplay_data, &tile_data), vec![east]);
+        move_dragon(&mut gameplay_data, &tile_data, &mut players, east);
+        assert_eq!(gameplay_data.dragon_moves_left, 0);
+        assert!(gameplay_data.area_meeples.contains_key(&road_area));
+    }
+
+    #[test]
+    fn princess_sends_a_knight_home() {
+        let tile_data = create_tiles(&[Expansion::PrincessAndDragon]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(&["a".to_string(), "b".to_string()], &[None, None], &[]);
+        let town = nth_tile(&tile_data, TileType::FFFT, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            town,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let town_area = area_of(&tile_data, town, AreaType::Town);
+        let knight = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, town_area, knight);
+        // A plain tile joining the town can't remove anyone.
+        let plain = nth_tile(&tile_data, TileType::TFTF_02, 0);
+        let north = TileMatrixCoords { x: 0, y: 1 };
+        let (_, dir) = *get_legal_placements(&gameplay_data, &tile_data, plain)
+            .iter()
+            .find(|(coords, _)| *coords == north)
+            .unwrap();
+        let mut plain_board = gameplay_data.clone();
+        place_tile(&mut plain_board, &tile_data, plain, dir, north);
+        assert!(!princess_can_remove(&plain_board, &tile_data, town_area));
+
+        let princess = nth_tile(&tile_data, TileType::FFFT_Princess, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            princess,
+            TileDirection::DOWN,
+            0,
+            1,
+        );
+        assert!(princess_can_remove(&gameplay_data, &tile_data, town_area));
+        assert_eq!(
+            remove_meeple(&mut gameplay_data, &mut players, town_area).map(|m| m.player),
+            Some(1)
+        );
+        assert_eq!(players.players[1].meeples.len(), NUM_MEEPLES);
+    }
+
+    #[test]
+    fn abbey_closes_the_features_around_it() {
+        let tile_data = create_tiles(&[Expansion::AbbeyAndMayor]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        // A road ending below the hole, a town above it and cloisters either side.
+        let road_end = nth_tile(&tile_data, TileType::FRFF_C, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road_end,
+            TileDirection::DOWN,
+            0,
+            -1,
+        );
+        let town = nth_tile(&tile_data, TileType::FFFT, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            town,
+            TileDirection::DOWN,
+            0,
+            1,
+        );
+        let cloister = nth_tile(&tile_data, TileType::FFFF_C, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            cloister,
+            TileDirection::UP,
+            -1,
+            0,
+        );
+        let cloister2 = nth_tile(&tile_data, TileType::FFFF_C, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            cloister2,
+            TileDirection::UP,
+            1,
+            0,
+        );
+
+        let abbey = gameplay_data.abbey_tiles[0];
+        let placements = get_legal_placements(&gameplay_data, &tile_data, abbey);
+        assert!(!placements.is_empty());
+        assert!(placements
+            .iter()
+            .all(|(coords, _)| *coords == TileMatrixCoords { x: 0, y: 0 }));
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            abbey,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let completed = find_completed_features(&gameplay_data, &tile_data, abbey);
+        let road_area = area_of(&tile_data, road_end, AreaType::EndRoad);
+        let town_area = area_of(&tile_data, town, AreaType::Town);
+        assert_eq!(completed.len(), 2);
+        assert!(completed
+            .iter()
+            .any(|f| f.feature_type == FeatureType::Road && f.areas.contains(&road_area)));
+        assert!(completed
+            .iter()
+            .any(|f| f.feature_type == FeatureType::Town && f.areas.contains(&town_area)));
+    }
+
+    #[test]
+    fn wagon_moves_on_from_a_completed_road() {
+        let tile_data = create_tiles(&[Expansion::AbbeyAndMayor]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::AbbeyAndMayor],
+        );
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let wagon = PlacedMeeple {
+            meeple: wagon_index(0),
+            player: 0,
+        };
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            road_area,
+            wagon
+        ));
+        place_meeple(&mut gameplay_data, &mut players, road_area, wagon);
+        let crossing = nth_tile(&tile_data, TileType::RRRF, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            crossing,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        let crossing2 = nth_tile(&tile_data, TileType::RRRF, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            crossing2,
+            TileDirection::UP,
+            -1,
+            0,
+        );
+        let completed = find_completed_features(&gameplay_data, &tile_data, crossing2);
+        return_meeples_from_feature(&completed[0], &mut gameplay_data, &mut players);
+        assert_eq!(gameplay_data.pending_wagons.len(), 1);
+
+        // Onto the crossings' other roads, never back onto the road just scored.
+        let (_, from_area) = gameplay_data.pending_wagons[0];
+        let destinations = get_wagon_destinations(&gameplay_data, &tile_data, from_area);
+        assert_eq!(destinations.len(), 4);
+        assert!(destinations.iter().all(|a| !completed[0].areas.contains(a)));
+        keep_movable_wagons(&mut gameplay_data, &tile_data);
+        assert_eq!(gameplay_data.pending_wagons.len(), 1);
+    }
+
+    #[test]
+    fn abbey_is_played_once_into_a_hole() {
+        let tile_data = create_tiles(&[Expansion::AbbeyAndMayor]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::AbbeyAndMayor],
+        );
+        assert_eq!(gameplay_data.abbey_tiles.len(), MAX_PLAYERS + 1);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        // No hole on the board yet.
+        assert_eq!(
+            take_abbey(&mut gameplay_data, &tile_data, &mut players),
+            None
+        );
+
+        let neighbors = [
+            (TileType::RFRF_02, 1, 2, 0),
+            (TileType::FFFF_C, 0, 1, 1),
+            (TileType::FFFF_C, 1, 1, -1),
+        ];
+        for (tile_type, n, x, y) in neighbors {
+            let tile_idx = nth_tile(&tile_data, tile_type, n);
+            place_at(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                x,
+                y,
+            );
+        }
+        let abbey = take_abbey(&mut gameplay_data, &tile_data, &mut players).unwrap();
+        assert!(tile_type_is_abbey(tile_data.all_tiles[abbey].tile_type));
+        assert!(!players.current().has_abbey);
+        assert_eq!(
+            take_abbey(&mut gameplay_data, &tile_data, &mut players),
+            None
+        );
+        let hole = TileMatrixCoords { x: 1, y: 0 };
+        assert!(get_legal_placements(&gameplay_data, &tile_data, abbey)
+            .iter()
+            .all(|(coords, _)| *coords == hole));
+    }
+
+    // A tower foundation at the origin with two plain roads east of it. Returns the board, the
+    // players and the road areas of the two plain tiles.
+    fn tower_board(tile_data: &GameTileData) -> (GameplayData, Players, [TileAreaIndex; 2]) {
+        let mut gameplay_data = GameplayData::new(tile_data, 7);
+        let players = Players::new(
+            &["a".to_string(), "b".to_string(), "c".to_string()],
+            &[None, None, None],
+            &[Expansion::Tower],
+        );
+        let tower = nth_tile(tile_data, TileType::RFRF_02_Tower, 0);
+        place_at(
+            &mut gameplay_data,
+            tile_data,
+            tower,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let mut roads = [0; 2];
+        for (n, road_area) in roads.iter_mut().enumerate() {
+            let road = nth_tile(tile_data, TileType::RFRF_02, n);
+            place_at(
+                &mut gameplay_data,
+                tile_data,
+                road,
+                TileDirection::UP,
+                n as i32 + 1,
+                0,
+            );
+            *road_area = area_of(tile_data, road, AreaType::Road);
+        }
+        return (gameplay_data, players, roads);
+    }
+
+    #[test]
+    fn tower_sees_as_far_as_it_is_tall() {
+        let tile_data = create_tiles(&[Expansion::Tower]);
+        let (mut gameplay_data, mut players, roads) = tower_board(&tile_data);
+        let far_meeple = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, roads[1], far_meeple);
+        let plain = TileMatrixCoords { x: 1, y: 0 };
+        assert_eq!(
+            build_tower(&mut gameplay_data, &tile_data, &mut players, plain),
+            None
+        );
+        let origin = TileMatrixCoords { x: 0, y: 0 };
+        assert_eq!(
+            build_tower(&mut gameplay_data, &tile_data, &mut players, origin),
+            Some(1)
+        );
+        assert!(get_tower_captures(&gameplay_data, &tile_data, origin).is_empty());
+        assert_eq!(
+            build_tower(&mut gameplay_data, &tile_data, &mut players, origin),
+            Some(2)
+        );
+        assert_eq!(players.players[0].tower_pieces, 7);
+        assert_eq!(gameplay_data.tower_built, Some(origin));
+        assert_eq!(
+            get_tower_captures(&gameplay_data, &tile_data, origin),
+            vec![roads[1]]
+        );
+        players.players[0].tower_pieces = 0;
+        assert_eq!(
+            build_tower(&mut gameplay_data, &tile_data, &mut players, origin),
+            None
+        );
+    }
+
+    #[test]
+    fn prisoners_are_exchanged_one_for_one() {
+        let tile_data = create_tiles(&[Expansion::Tower]);
+        let (mut gameplay_data, mut players, roads) = tower_board(&tile_data);
+        let meeple_of = |player: PlayerIndex| PlacedMeeple {
+            meeple: player * NUM_MEEPLES,
+            player,
+        };
+        place_meeple(&mut gameplay_data, &mut players, roads[1], meeple_of(1));
+        let (captured, released) = capture_meeple(&mut gameplay_data, &mut players, roads[1]);
+        assert_eq!(captured.map(|m| m.player), Some(1));
+        assert!(released.is_empty());
+        assert_eq!(players.players[0].prisoners.len(), 1);
+        assert!(!players.players[1].meeples.contains(&NUM_MEEPLES));
+
+        // Player 1 taking one of player 0's meeples gets both sent home.
+        place_meeple(&mut gameplay_data, &mut players, roads[0], meeple_of(0));
+        players.current_player = 1;
+        let (_, released) = capture_meeple(&mut gameplay_data, &mut players, roads[0]);
+        assert_eq!(released.len(), 2);
+        assert!(players.players[0].prisoners.is_empty());
+        assert!(players.players[1].prisoners.is_empty());
+        assert!(players.players[0].meeples.contains(&0));
+        assert!(players.players[1].meeples.contains(&NUM_MEEPLES));
+
+        // A player's own meeple just goes home.
+        place_meeple(&mut gameplay_data, &mut players, roads[1], meeple_of(1));
+        let (_, released) = capture_meeple(&mut gameplay_data, &mut players, roads[1]);
+        assert!(released.is_empty());
+        assert!(players.players[1].prisoners.is_empty());
+        assert!(players.players[1].meeples.contains(&NUM_MEEPLES));
+    }
+
+    #[test]
+    fn ransom_buys_a_prisoner_back() {
+        let tile_data = create_tiles(&[Expansion::Tower]);
+        let (mut gameplay_data, mut players, roads) = tower_board(&tile_data);
+        let meeple = PlacedMeeple {
+            meeple: 1,
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, roads[0], meeple);
+        players.current_player = 1;
+        capture_meeple(&mut gameplay_data, &mut players, roads[0]);
+        players.current_player = 0;
+        // Not enough points yet.
+        assert!(pay_ransom(&mut players).is_none());
+        players.players[0].points = 5;
+        assert_eq!(pay_ransom(&mut players).map(|m| m.meeple), Some(1));
+        assert_eq!(players.players[0].points, 5 - RANSOM_POINTS);
+        assert_eq!(players.players[1].points, RANSOM_POINTS);
+        assert!(players.players[0].meeples.contains(&1));
+        assert!(pay_ransom(&mut players).is_none());
+    }
+
+    #[test]
+    fn only_followers_keep_meeples_out() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep, Expansion::TradersAndBuilders]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::HillsAndSheep, Expansion::TradersAndBuilders],
+        );
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let road2 = nth_tile(&tile_data, TileType::RFRF_02, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road2,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        let farm = area_of(&tile_data, road, AreaType::Farm);
+        let farm2 = *get_feature(&gameplay_data, &tile_data, FeatureType::Farm, farm)
+            .areas
+            .iter()
+            .find(|a| tile_data.all_areas[**a].tile_idx == road2)
+            .unwrap();
+
+        // A shepherd shares its field with farmers, but not its own spot.
+        let shepherd = PlacedMeeple {
+            meeple: shepherd_index(0),
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, farm, shepherd);
+        assert!(!meeple_can_be_placed(&gameplay_data, &tile_data, farm));
+        assert!(meeple_can_be_placed(&gameplay_data, &tile_data, farm2));
+        let farmer = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, farm2, farmer);
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farm2,
+            PlacedMeeple {
+                meeple: 0,
+                player: 0,
+            }
+        ));
+
+        // A builder left on its own doesn't hold the road either.
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        let road2_area = area_of(&tile_data, road2, AreaType::Road);
+        let meeple = PlacedMeeple {
+            meeple: 0,
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, road_area, meeple);
+        let builder = PlacedMeeple {
+            meeple: builder_index(0),
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, road2_area, builder);
+        remove_meeple(&mut gameplay_data, &mut players, road_area);
+        assert!(meeple_can_be_placed(&gameplay_data, &tile_data, road_area));
+        assert!(!meeple_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            road2_area
+        ));
+    }
+
+    // A shepherd of player 0 in the field of a lone tile at the origin.
+    fn shepherd_board(tile_data: &GameTileData) -> (GameplayData, Players, TileAreaIndex) {
+        let mut gameplay_data = GameplayData::new(tile_data, 7);
+        let mut players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::HillsAndSheep],
+        );
+        let tile_idx = nth_tile(tile_data, TileType::FFFT_Hill, 0);
+        place_at(
+            &mut gameplay_data,
+            tile_data,
+            tile_idx,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let farm = area_of(tile_data, tile_idx, AreaType::Farm);
+        let shepherd = PlacedMeeple {
+            meeple: shepherd_index(0),
+            player: 0,
+        };
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            tile_data,
+            farm,
+            shepherd
+        ));
+        place_meeple(&mut gameplay_data, &mut players, farm, shepherd);
+        return (gameplay_data, players, farm);
+    }
+
+    #[test]
+    fn shepherd_grows_a_flock_from_the_seeded_bag() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep]);
+        let (mut gameplay_data, mut players, farm) = shepherd_board(&tile_data);
+        assert_eq!(gameplay_data.sheep_bag.len(), 18);
+        let other_shepherd = PlacedMeeple {
+            meeple: shepherd_index(1),
+            player: 1,
+        };
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farm,
+            other_shepherd
+        ));
+
+        // The same seed draws the same tokens.
+        let mut replay = (gameplay_data.clone(), players.clone());
+        for _ in 0..5 {
+            let (token, _) = grow_flock(&mut gameplay_data, &mut players, farm);
+            let (replayed, _) = grow_flock(&mut replay.0, &mut replay.1, farm);
+            assert_eq!(token, replayed);
+        }
+
+        // The next tile in the field lets the shepherd tend its flock.
+        let tile_idx = nth_tile(&tile_data, TileType::FFFT_Hill, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            tile_idx,
+            TileDirection::DOWN,
+            0,
+            -1,
+        );
+        assert_eq!(
+            get_flock_to_tend(&gameplay_data, &tile_data, tile_idx, 0),
+            Some(farm)
+        );
+        assert_eq!(
+            get_flock_to_tend(&gameplay_data, &tile_data, tile_idx, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn wolf_sends_the_shepherd_home() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep]);
+        let (mut gameplay_data, mut players, farm) = shepherd_board(&tile_data);
+        gameplay_data.sheep_bag = vec![SheepToken::Wolf];
+        gameplay_data
+            .flocks
+            .insert(farm, vec![SheepToken::Sheep(4)]);
+        let (token, home) = grow_flock(&mut gameplay_data, &mut players, farm);
+        assert_eq!(token, Some(SheepToken::Wolf));
+        assert_eq!(home.len(), 1);
+        assert!(gameplay_data.flocks.is_empty());
+        assert_eq!(gameplay_data.sheep_bag.len(), 2);
+        assert!(players.players[0].meeples.contains(&shepherd_index(0)));
+        assert_eq!(players.players[0].points, 0);
+    }
+
+    #[test]
+    fn shepherd_drives_the_flock_home_for_points() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep]);
+        let (mut gameplay_data, mut players, farm) = shepherd_board(&tile_data);
+        let num_tokens = gameplay_data.sheep_bag.len();
+        let flock = vec![SheepToken::Sheep(3), SheepToken::Sheep(2)];
+        gameplay_data.flocks.insert(farm, flock);
+        let home = drive_flock_home(&mut gameplay_data, &mut players, farm);
+        assert_eq!(home.len(), 1);
+        assert_eq!(players.players[0].points, 5);
+        assert_eq!(gameplay_data.sheep_bag.len(), num_tokens + 2);
+        assert!(!gameplay_data.area_meeples.contains_key(&farm));
+    }
+
+    fn three_bazaar_players() -> Players {
+        return Players::new(
+            &["a".to_string(), "b".to_string(), "c".to_string()],
+            &[None, None, None],
+            &[Expansion::BridgesCastlesBazaars],
+        );
+    }
+
+    #[test]
+    fn bazaar_sells_buys_and_gives_away_tiles() {
+        let tile_data = create_tiles(&[Expansion::BridgesCastlesBazaars]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 3);
+        let mut players = three_bazaar_players();
+        let num_tiles = gameplay_data.unspawned_tiles.len();
+        assert!(open_bazaar(&mut gameplay_data, &mut players));
+        assert_eq!(gameplay_data.market.len(), 3);
+        assert_eq!(gameplay_data.unspawned_tiles.len(), num_tiles - 3);
+        let auction = gameplay_data.auction.clone().unwrap();
+        assert_eq!(auction.auctioneer, 1);
+        assert_eq!(auction.bidders, vec![2, 0]);
+
+        players.players[0].points = 5;
+        players.players[2].points = 5;
+        bid_in_auction(&mut gameplay_data, &players, 3);
+        // More than the bidder has doesn't count.
+        bid_in_auction(&mut gameplay_data, &players, 9);
+        assert_eq!(gameplay_data.auction.as_ref().unwrap().high_bidder, 2);
+        // The auctioneer can't pay 3, so has to sell.
+        assert!(!close_auction(&mut gameplay_data, &mut players, true));
+        assert!(gameplay_data.auction.is_some());
+        assert!(close_auction(&mut gameplay_data, &mut players, false));
+        assert_eq!(players.players[1].points, 3);
+        assert_eq!(players.players[2].points, 2);
+        assert_eq!(players.players[2].bought_tiles.len(), 1);
+
+        // Nobody outbids the auctioneer, who gets the tile for free, and the last player
+        // gets the last one.
+        let auction = gameplay_data.auction.clone().unwrap();
+        assert_eq!((auction.auctioneer, auction.bidders), (1, vec![0]));
+        bid_in_auction(&mut gameplay_data, &players, 0);
+        assert!(close_auction(&mut gameplay_data, &mut players, true));
+        assert_eq!(players.players[1].points, 3);
+        assert!(gameplay_data.auction.is_none());
+        assert!(gameplay_data.market.is_empty());
+        assert!(players.players.iter().all(|p| p.bought_tiles.len() == 1));
+    }
+
+    #[test]
+    fn market_takes_tiles_nobody_can_place_yet() {
+        let expansions = [Expansion::BridgesCastlesBazaars, Expansion::AbbeyAndMayor];
+        let tile_data = create_tiles(&expansions);
+        let mut gameplay_data = GameplayData::new(&tile_data, 3);
+        let mut players = three_bazaar_players();
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        // Abbeys fit nowhere on a board without holes.
+        let abbeys = gameplay_data.abbey_tiles[..3].to_vec();
+        gameplay_data.unspawned_tiles = abbeys.clone();
+        assert!(!tile_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            &players,
+            abbeys[0]
+        ));
+        open_bazaar(&mut gameplay_data, &mut players);
+        let mut market_tiles: Vec<TileIndex> = players
+            .players
+            .iter()
+            .flat_map(|p| p.bought_tiles.clone())
+            .chain(gameplay_data.market.clone())
+            .collect();
+        market_tiles.sort();
+        assert_eq!(market_tiles, abbeys);
+        assert!(gameplay_data.discarded_tiles.is_empty());
+    }
+
+    #[test]
+    fn bought_bazaar_opens_no_bazaar() {
+        let tile_data = create_tiles(&[Expansion::BridgesCastlesBazaars]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 3);
+        let mut players = three_bazaar_players();
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        let bazaar = nth_tile(&tile_data, TileType::RFRF_02_Bazaar, 0);
+        players.players[0].bought_tiles.push(bazaar);
+        let bought = take_bought_tile(&mut gameplay_data, &tile_data, &mut players);
+        assert_eq!(bought, Some(bazaar));
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            bazaar,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        assert!(!gameplay_data.bazaar_pending);
+
+        // A bazaar drawn from the bag still opens one.
+        let bazaar2 = nth_tile(&tile_data, TileType::RFRF_02_Bazaar, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            bazaar2,
+            TileDirection::UP,
+            2,
+            0,
+        );
+        assert!(gameplay_data.bazaar_pending);
+    }
+
+    #[test]
+    fn bridge_carries_a_road_over_a_field() {
+        let tile_data = create_tiles(&[Expansion::BridgesCastlesBazaars]);
+        let mut tile_data_mut = tile_data.clone();
+        let mut gameplay_data = GameplayData::new(&tile_data, 3);
+        let mut players = three_bazaar_players();
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::RIGHT,
+            0,
+            0,
+        );
+        // The road runs north into the field of an unturned road tile.
+        let crossing = nth_tile(&tile_data, TileType::RFRF_02, 1);
+        let coords = TileMatrixCoords { x: 0, y: 1 };
+        assert!(!tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            crossing,
+            TileDirection::UP,
+            coords
+        ));
+        let axis = get_bridge_for_fit(
+            &gameplay_data,
+            &tile_data,
+            &players,
+            crossing,
+            TileDirection::UP,
+            coords,
+        );
+        assert_eq!(axis, Some(BridgeAxis::NorthSouth));
+
+        place_tile(
+            &mut gameplay_data,
+            &tile_data,
+            crossing,
+            TileDirection::UP,
+            coords,
+        );
+        let bridge = place_bridge(
+            &mut gameplay_data,
+            &mut tile_data_mut,
+            &mut players,
+            crossing,
+            BridgeAxis::NorthSouth,
+        );
+        assert_eq!(players.current().bridges, 2);
+        let feature = get_feature(
+            &gameplay_data,
+            &tile_data_mut,
+            FeatureType::Road,
+            area_of(&tile_data, road, AreaType::Road),
+        );
+        assert!(feature.areas.contains(&bridge));
+        assert!(feature.tiles.contains(&crossing));
+
+        // Without bridges left the tile doesn't fit.
+        players.players[players.current_player].bridges = 0;
+        let other = nth_tile(&tile_data, TileType::RFRF_02, 2);
+        let coords = TileMatrixCoords { x: 0, y: -1 };
+        assert!(get_bridge_for_fit(
+            &gameplay_data,
+            &tile_data_mut,
+            &players,
+            other,
+            TileDirection::UP,
+            coords
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn only_a_small_held_town_becomes_a_castle() {
+        let tile_data = create_tiles(&[Expansion::BridgesCastlesBazaars]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 3);
+        let mut players = three_bazaar_players();
+        let town_a = nth_tile(&tile_data, TileType::FFFT, 0);
+        let town_b = nth_tile(&tile_data, TileType::FFFT, 1);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            town_a,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            town_b,
+            TileDirection::DOWN,
+            0,
+            1,
+        );
+        let town_area = area_of(&tile_data, town_a, AreaType::Town);
+        let town = get_feature(&gameplay_data, &tile_data, FeatureType::Town, town_area);
+        assert!(!feature_can_become_castle(&gameplay_data, &town));
+
+        let knight = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, town_area, knight);
+        assert!(feature_can_become_castle(&gameplay_data, &town));
+        build_castle(&mut gameplay_data, &town);
+        assert!(!gameplay_data.area_meeples.contains_key(&town_area));
+        assert_eq!(gameplay_data.castles.len(), 1);
+        assert_eq!(gameplay_data.castles[0].meeple.player, 1);
+
+        // A second knight makes it too crowded.
+        let other_area = area_of(&tile_data, town_b, AreaType::Town);
+        place_meeple(&mut gameplay_data, &mut players, town_area, knight);
+        place_meeple(
+            &mut gameplay_data,
+            &mut players,
+            other_area,
+            PlacedMeeple {
+                meeple: 0,
+                player: 0,
+            },
+        );
+        assert!(!feature_can_become_castle(&gameplay_data, &town));
+    }
+
+    fn garden_board(tile_data: &GameTileData) -> (GameplayData, Players, TileAreaIndex) {
+        let mut gameplay_data = GameplayData::new(tile_data, 7);
+        let players = Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            &[Expansion::GardensAndAbbot],
+        );
+        let garden = nth_tile(tile_data, TileType::FFFF_Garden, 0);
+        place_at(
+            &mut gameplay_data,
+            tile_data,
+            garden,
+            TileDirection::UP,
+            0,
+            0,
+        );
+        return (
+            gameplay_data,
+            players,
+            area_of(tile_data, garden, AreaType::Garden),
+        );
+    }
+
+    #[test]
+    fn abbot_goes_to_gardens_and_cloisters_only() {
+        let tile_data = create_tiles(&[Expansion::GardensAndAbbot]);
+        let (mut gameplay_data, players, garden) = garden_board(&tile_data);
+        assert!(players.players[0].meeples.contains(&abbot_index(0)));
+        let abbot = PlacedMeeple {
+            meeple: abbot_index(0),
+            player: 0,
+        };
+        let follower = PlacedMeeple {
+            meeple: 0,
+            player: 0,
+        };
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            garden,
+            abbot
+        ));
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            garden,
+            follower
+        ));
+        let garden_tile = tile_data.all_areas[garden].tile_idx;
+        let farm = area_of(&tile_data, garden_tile, AreaType::Farm);
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farm,
+            abbot
+        ));
+
+        let cloister = nth_tile(&tile_data, TileType::FFFF_C, 0);
+        place_at(
+            &mut gameplay_data,
+            &tile_data,
+            cloister,
+            TileDirection::UP,
+            1,
+            0,
+        );
+        let cloister_area = area_of(&tile_data, cloister, AreaType::Cloister);
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            cloister_area,
+            abbot
+        ));
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            cloister_area,
+            follower
+        ));
+    }
+
+    #[test]
+    fn recalled_abbot_scores_its_garden_as_it_stands() {
+        let tile_data = create_tiles(&[Expansion::GardensAndAbbot]);
+        let (mut gameplay_data, mut players, garden) = garden_board(&tile_data);
+        assert!(recall_abbot(&mut gameplay_data, &tile_data, &mut players).is_none());
+        let abbot = PlacedMeeple {
+            meeple: abbot_index(0),
+            player: 0,
+        };
+        place_meeple(&mut gameplay_data, &mut players, garden, abbot);
+        for (n, x) in [1, -1].into_iter().enumerate() {
+            let cloister = nth_tile(&tile_data, TileType::FFFF_C, n);
+            place_at(
+                &mut gameplay_data,
+                &tile_data,
+                cloister,
+                TileDirection::UP,
+                x,
+                0,
+            );
+        }
+        let (recalled, points) =
+            recall_abbot(&mut gameplay_data, &tile_data, &mut players).unwrap();
+        assert_eq!(
+            (recalled.meeple, recalled.player, points),
+            (abbot.meeple, 0, 3)
+        );
+        assert_eq!(players.players[0].points, 3);
+        assert!(players.players[0].meeples.contains(&abbot_index(0)));
+        assert!(!gameplay_data.area_meeples.contains_key(&garden));
+    }
+
+    #[test]
+    fn garden_completes_like_a_cloister() {
+        let tile_data = create_tiles(&[Expansion::GardensAndAbbot]);
+        let (mut gameplay_data, _, garden) = garden_board(&tile_data);
+        // Cloisters to the sides and below, towns facing away above and in the bottom corners.
+        let mut neighbors = vec![];
+        for (n, (x, y)) in [(-1, 0), (1, 0), (0, -1)].into_iter().enumerate() {
+            neighbors.push((
+                nth_tile(&tile_data, TileType::FFFF_C, n),
+                TileDirection::UP,
+                x,
+                y,
+            ));
+        }
+        for (n, (x, y)) in [(-1, 1), (0, 1), (1, 1)].into_iter().enumerate() {
+            neighbors.push((
+                nth_tile(&tile_data, TileType::FFFT, n),
+                TileDirection::UP,
+                x,
+                y,
+            ));
+        }
+        for (n, (x, y)) in [(-1, -1), (1, -1)].into_iter().enumerate() {
+            neighbors.push((
+                nth_tile(&tile_data, TileType::FFFT, 3 + n),
+                TileDirection::DOWN,
+                x,
+                y,
+            ));
+        }
+        let mut completed = vec![];
+        for (tile_idx, dir, x, y) in neighbors {
+            place_at(&mut gameplay_data, &tile_data, tile_idx, dir, x, y);
+            completed = find_completed_features(&gameplay_data, &tile_data, tile_idx);
+        }
+        let garden_feature = completed.iter().find(|f| f.areas == vec![garden]).unwrap();
+        assert_eq!(garden_feature.feature_type, FeatureType::Cloister);
+        assert_eq!(garden_feature.tiles.len(), 9);
+    }
+}
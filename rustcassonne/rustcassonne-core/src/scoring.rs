@@ -0,0 +1,931 @@
+use std::collections::HashMap;
+
+use crate::game_board::*;
+use crate::players::*;
+use crate::rules::*;
+use crate::tiles::*;
+
+fn count_pennants(feature: &Feature, tile_data: &GameTileData) -> i32 {
+    return feature
+        .areas
+        .iter()
+        .filter(|a| tile_data.all_areas[**a].area_type == AreaType::PennantTown)
+        .count() as i32;
+}
+
+fn count_vineyards(feature: &Feature, tile_data: &GameTileData) -> i32 {
+    return feature
+        .tiles
+        .iter()
+        .filter(|t| tile_type_has_vineyard(tile_data.all_tiles[**t].tile_type))
+        .count() as i32;
+}
+
+fn feature_has_area_type(feature: &Feature, tile_data: &GameTileData, area_type: AreaType) -> bool {
+    return feature
+        .areas
+        .iter()
+        .any(|a| tile_data.all_areas[*a].area_type == area_type);
+}
+
+// Points a feature is worth when it completes, or at the end of the game if it didn't.
+// Roads with an inn and towns with a cathedral score more when completed, nothing otherwise.
+pub fn score_feature(feature: &Feature, tile_data: &GameTileData, completed: bool) -> i32 {
+    let num_tiles = feature.tiles.len() as i32;
+    match feature.feature_type {
+        FeatureType::Road => {
+            if !feature_has_area_type(feature, tile_data, AreaType::InnRoad) {
+                num_tiles
+            } else if completed {
+                2 * num_tiles
+            } else {
+                0
+            }
+        }
+        FeatureType::Town => {
+            let num_pennants = count_pennants(feature, tile_data);
+            if feature_has_area_type(feature, tile_data, AreaType::CathedralTown) {
+                if completed {
+                    3 * num_tiles + 3 * num_pennants
+                } else {
+                    0
+                }
+            } else if completed {
+                2 * num_tiles + 2 * num_pennants
+            } else {
+                num_tiles + num_pennants
+            }
+        }
+        // The cloister tile plus its neighbors, so 9 once it is surrounded. Gardens score the
+        // same way. Vineyards around a completed cloister add 3 each.
+        FeatureType::Cloister => {
+            if completed && feature_has_area_type(feature, tile_data, AreaType::Cloister) {
+                num_tiles + 3 * count_vineyards(feature, tile_data)
+            } else {
+                num_tiles
+            }
+        }
+        // Farms are only scored at the end of the game, see score_farms.
+        FeatureType::Farm => 0,
+    }
+}
+
+// Players with the most meeples on the feature. Tied players all get the full points, unless
+// some of them have a meeple on a hill: then only those do. Builders, pigs, barns and
+// shepherds don't count, and a mayor counts as many as its town's pennants.
+pub fn get_feature_owners(
+    feature: &Feature,
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+) -> Vec<PlayerIndex> {
+    let mut meeple_counts: HashMap<PlayerIndex, usize> = HashMap::new();
+    let mut on_hill: Vec<PlayerIndex> = vec![];
+    for area_idx in &feature.areas {
+        if let Some(placed_meeple) = gameplay_data.area_meeples.get(area_idx) {
+            let strength = match figure_kind(placed_meeple.meeple) {
+                FigureKind::Mayor => count_pennants(feature, tile_data) as usize,
+                _ => meeple_strength(placed_meeple.meeple),
+            };
+            if strength > 0 {
+                *meeple_counts.entry(placed_meeple.player).or_insert(0) += strength;
+                let tile_idx = tile_data.all_areas[*area_idx].tile_idx;
+                if tile_type_has_hill(tile_data.all_tiles[tile_idx].tile_type) {
+                    on_hill.push(placed_meeple.player);
+                }
+            }
+        }
+    }
+    let Some(&most_meeples) = meeple_counts.values().max() else {
+        return vec![];
+    };
+    let mut owners: Vec<PlayerIndex> = meeple_counts
+        .iter()
+        .filter(|(_, count)| **count == most_meeples)
+        .map(|(player, _)| *player)
+        .collect();
+    if owners.len() > 1 && owners.iter().any(|p| on_hill.contains(p)) {
+        owners.retain(|p| on_hill.contains(p));
+    }
+    owners.sort();
+    return owners;
+}
+
+pub fn award_points(players: &mut [Player], owners: &[PlayerIndex], points: i32) {
+    for owner in owners {
+        players[*owner].points += points;
+    }
+}
+
+// The fairy's meeple is worth 3 more points to its owner when its feature is scored.
+pub fn score_fairy_feature(
+    feature: &Feature,
+    gameplay_data: &GameplayData,
+    players: &mut [Player],
+) {
+    let Some(fairy_area) = gameplay_data.fairy else {
+        return;
+    };
+    if !feature.areas.contains(&fairy_area) {
+        return;
+    }
+    if let Some(owner) = get_fairy_owner(gameplay_data) {
+        players[owner].points += 3;
+    }
+}
+
+// Gives the points for a just completed feature to its owners and returns them.
+pub fn score_completed_feature(
+    feature: &Feature,
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    players: &mut [Player],
+) -> Vec<PlayerIndex> {
+    let owners = get_feature_owners(feature, gameplay_data, tile_data);
+    award_points(players, &owners, score_feature(feature, tile_data, true));
+    score_fairy_feature(feature, gameplay_data, players);
+    return owners;
+}
+
+// A cloister only counts for a castle if the cloister itself is in the fief, other features
+// if any of their tiles is.
+fn feature_is_in_fief(feature: &Feature, tile_data: &GameTileData, fief: &[TileIndex]) -> bool {
+    if feature.feature_type == FeatureType::Cloister {
+        return feature
+            .areas
+            .iter()
+            .any(|a| fief.contains(&tile_data.all_areas[*a].tile_idx));
+    }
+    return feature.tiles.iter().any(|t| fief.contains(t));
+}
+
+// Each castle with one of the features just completed in its fief scores the best of them
+// for its owner, and its meeple goes home. Returns the meeples that went home.
+pub fn score_castles(
+    features: &[Feature],
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+) -> Vec<PlacedMeeple> {
+    let mut returned_meeples: Vec<PlacedMeeple> = vec![];
+    let castles = std::mem::take(&mut gameplay_data.castles);
+    for castle in castles {
+        let surroundings = get_castle_surroundings(gameplay_data, &castle);
+        let best_points = features
+            .iter()
+            .filter(|f| feature_is_in_fief(f, tile_data, &surroundings))
+            .map(|f| score_feature(f, tile_data, true))
+            .max();
+        let Some(points) = best_points else {
+            gameplay_data.castles.push(castle);
+            continue;
+        };
+        let owner = &mut players.players[castle.meeple.player];
+        owner.points += points;
+        owner.meeples.push(castle.meeple.meeple);
+        returned_meeples.push(castle.meeple);
+    }
+    return returned_meeples;
+}
+
+fn get_placed_tiles(gameplay_data: &GameplayData) -> Vec<TileIndex> {
+    let mut placed_tiles: Vec<TileIndex> = gameplay_data
+        .board_tile_matrix_inverse
+        .keys()
+        .copied()
+        .filter(|t| *t < PLACEHOLDER_TILE_OFFSET)
+        .collect();
+    placed_tiles.sort();
+    return placed_tiles;
+}
+
+// Every road, town and cloister on the board that is still unfinished.
+pub fn get_incomplete_features(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+) -> Vec<Feature> {
+    let mut incomplete_features: Vec<Feature> = vec![];
+    let mut checked_areas: Vec<TileAreaIndex> = vec![];
+    for tile_idx in get_placed_tiles(gameplay_data) {
+        for area_idx in &tile_data.all_tiles[tile_idx].areas {
+            if checked_areas.contains(area_idx) {
+                continue;
+            }
+            let Some(feature_type) =
+                area_type_to_feature_type(tile_data.all_areas[*area_idx].area_type)
+            else {
+                continue;
+            };
+
+            if feature_type == FeatureType::Cloister {
+                let coords = gameplay_data.board_tile_matrix_inverse[&tile_idx];
+                let tiles = get_cloister_tiles(gameplay_data, coords);
+                if tiles.len() < SURROUNDING_COORDS.len() + 1 {
+                    incomplete_features.push(Feature {
+                        feature_type,
+                        tiles,
+                        areas: vec![*area_idx],
+                    });
+                }
+                continue;
+            }
+            if feature_type == FeatureType::Farm {
+                continue;
+            }
+
+            let feature = get_feature(gameplay_data, tile_data, feature_type, *area_idx);
+            checked_areas.extend(&feature.areas);
+            if !feature_is_closed(gameplay_data, tile_data, &feature) {
+                incomplete_features.push(feature);
+            }
+        }
+    }
+    return incomplete_features;
+}
+
+// End of game scoring for everything left unfinished, at the reduced values.
+pub fn score_incomplete_features(
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+    players: &mut [Player],
+) {
+    for feature in get_incomplete_features(gameplay_data, tile_data) {
+        let owners = get_feature_owners(&feature, gameplay_data, tile_data);
+        award_points(players, &owners, score_feature(&feature, tile_data, false));
+    }
+}
+
+// Every field on the board, each made of farm areas connected across tiles.
+pub fn get_fields(gameplay_data: &GameplayData, tile_data: &GameTileData) -> Vec<Feature> {
+    let mut fields: Vec<Feature> = vec![];
+    let mut checked_areas: Vec<TileAreaIndex> = vec![];
+    for tile_idx in get_placed_tiles(gameplay_data) {
+        for area_idx in &tile_data.all_tiles[tile_idx].areas {
+            if checked_areas.contains(area_idx)
+                || tile_data.all_areas[*area_idx].area_type != AreaType::Farm
+            {
+                continue;
+            }
+            let field = get_feature(gameplay_data, tile_data, FeatureType::Farm, *area_idx);
+            checked_areas.extend(&field.areas);
+            fields.push(field);
+        }
+    }
+    return fields;
+}
+
+// Completed towns bordering the field, found through the connected_areas of its farms.
+pub fn get_supplied_towns(
+    field: &Feature,
+    gameplay_data: &GameplayData,
+    tile_data: &GameTileData,
+) -> Vec<Feature> {
+    let mut towns: Vec<Feature> = vec![];
+    let mut checked_areas: Vec<TileAreaIndex> = vec![];
+    for farm_area_idx in &field.areas {
+        for town_area_idx in &tile_data.all_areas[*farm_area_idx].connected_areas {
+            if checked_areas.contains(town_area_idx) {
+                continue;
+            }
+            let town = get_feature(gameplay_data, tile_data, FeatureType::Town, *town_area_idx);
+            checked_areas.extend(&town.areas);
+            if feature_is_closed(gameplay_data, tile_data, &town) {
+                towns.push(town);
+            }
+        }
+    }
+    return towns;
+}
+
+fn field_has_pig_of(field: &Feature, gameplay_data: &GameplayData, player: PlayerIndex) -> bool {
+    return field
+        .areas
+        .iter()
+        .filter_map(|a| gameplay_data.area_meeples.get(a))
+        .any(|m| m.player == player && figure_kind(m.meeple) == FigureKind::Pig);
+}
+
+fn get_barn_owners(field: &Feature, gameplay_data: &GameplayData) -> Vec<PlayerIndex> {
+    return field
+        .areas
+        .iter()
+        .filter_map(|a| gameplay_data.area_meeples.get(a))
+        .filter(|m| figure_kind(m.meeple) == FigureKind::Barn)
+        .map(|m| m.player)
+        .collect();
+}
+
+// End of game farmer scoring: 3 points for every completed town a field supplies, to the
+// players with the most farmers in it. An owner with their pig in the field gets 4. Fields
+// with a barn give its owner 4 instead, and only 1 to the farmers that came in after it.
+pub fn score_farms(gameplay_data: &GameplayData, tile_data: &GameTileData, players: &mut [Player]) {
+    for field in get_fields(gameplay_data, tile_data) {
+        let num_towns = get_supplied_towns(&field, gameplay_data, tile_data).len() as i32;
+        let barn_owners = get_barn_owners(&field, gameplay_data);
+        award_points(players, &barn_owners, 4 * num_towns);
+        let owners = get_feature_owners(&field, gameplay_data, tile_data);
+        if !barn_owners.is_empty() {
+            award_points(players, &owners, num_towns);
+            continue;
+        }
+        for owner in owners {
+            let points_per_town = if field_has_pig_of(&field, gameplay_data, owner) {
+                4
+            } else {
+                3
+            };
+            award_points(players, &[owner], points_per_town * num_towns);
+        }
+    }
+}
+
+// Goods symbols in the feature, indexed by Goods.
+pub fn count_goods(feature: &Feature, tile_data: &GameTileData) -> [usize; NUM_GOODS] {
+    let mut goods = [0; NUM_GOODS];
+    for area_idx in &feature.areas {
+        if let Some(g) = tile_data.all_areas[*area_idx].goods {
+            goods[g as usize] += 1;
+        }
+    }
+    return goods;
+}
+
+// Goods of a completed town go to whoever completed it, meeple or not.
+pub fn award_goods(feature: &Feature, tile_data: &GameTileData, player: &mut Player) {
+    for (held, gained) in player.goods.iter_mut().zip(count_goods(feature, tile_data)) {
+        *held += gained;
+    }
+}
+
+// End of game: 10 points for each kind of goods to the players holding the most of it.
+pub fn score_goods(players: &mut [Player]) {
+    for goods in GOODS {
+        let Some(most) = players.iter().map(|p| p.goods[goods as usize]).max() else {
+            continue;
+        };
+        if most == 0 {
+            continue;
+        }
+        for player in players.iter_mut() {
+            if player.goods[goods as usize] == most {
+                player.points += 10;
+            }
+        }
+    }
+}
+
+// A barn scores its field on the spot: the farmers already there get 3 points for every
+// completed town it supplies and go home. Returns the farmers taken off.
+pub fn score_barn_placement(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &mut Players,
+    barn_area: TileAreaIndex,
+) -> Vec<PlacedMeeple> {
+    let field = get_feature(gameplay_data, tile_data, FeatureType::Farm, barn_area);
+    let owners = get_feature_owners(&field, gameplay_data, tile_data);
+    let num_towns = get_supplied_towns(&field, gameplay_data, tile_data).len() as i32;
+    award_points(&mut players.players, &owners, 3 * num_towns);
+    let mut returned_meeples: Vec<PlacedMeeple> = vec![];
+    for area_idx in &field.areas {
+        let is_farmer = gameplay_data
+            .area_meeples
+            .get(area_idx)
+            .is_some_and(|m| figure_is_follower(m.meeple));
+        if is_farmer {
+            returned_meeples.extend(remove_meeple(gameplay_data, players, *area_idx));
+        }
+    }
+    return returned_meeples;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nth_tile(tile_data: &GameTileData, tile_type: TileType, n: usize) -> TileIndex {
+        return tile_data
+            .all_tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.tile_type == tile_type)
+            .nth(n)
+            .unwrap()
+            .0;
+    }
+
+    fn area_of(
+        tile_data: &GameTileData,
+        tile_idx: TileIndex,
+        area_type: AreaType,
+    ) -> TileAreaIndex {
+        return *tile_data.all_tiles[tile_idx]
+            .areas
+            .iter()
+            .find(|a| tile_data.all_areas[**a].area_type == area_type)
+            .unwrap();
+    }
+
+    // The feature of a lone tile put at the origin.
+    fn lone_feature(
+        tile_data: &GameTileData,
+        tile_type: TileType,
+        area_type: AreaType,
+        feature_type: FeatureType,
+    ) -> Feature {
+        let mut gameplay_data = GameplayData::new(tile_data, 7);
+        let tile_idx = nth_tile(tile_data, tile_type, 0);
+        place_tile(
+            &mut gameplay_data,
+            tile_data,
+            tile_idx,
+            TileDirection::UP,
+            TileMatrixCoords { x: 0, y: 0 },
+        );
+        let area_idx = area_of(tile_data, tile_idx, area_type);
+        return get_feature(&gameplay_data, tile_data, feature_type, area_idx);
+    }
+
+    fn two_players(expansions: &[Expansion]) -> Players {
+        return Players::new(
+            &["a".to_string(), "b".to_string()],
+            &[None, None],
+            expansions,
+        );
+    }
+
+    #[test]
+    fn towns_count_pennants() {
+        let tile_data = create_tiles(&[]);
+        let town = lone_feature(
+            &tile_data,
+            TileType::TFFT_03,
+            AreaType::Town,
+            FeatureType::Town,
+        );
+        assert_eq!(score_feature(&town, &tile_data, false), 1);
+        assert_eq!(score_feature(&town, &tile_data, true), 2);
+        let pennant_town = lone_feature(
+            &tile_data,
+            TileType::PFFP_03,
+            AreaType::PennantTown,
+            FeatureType::Town,
+        );
+        assert_eq!(score_feature(&pennant_town, &tile_data, false), 2);
+        assert_eq!(score_feature(&pennant_town, &tile_data, true), 4);
+    }
+
+    #[test]
+    fn inns_and_cathedrals_score_nothing_unfinished() {
+        let tile_data = create_tiles(&[Expansion::InnsAndCathedrals]);
+        let road = lone_feature(
+            &tile_data,
+            TileType::RFRF_02,
+            AreaType::Road,
+            FeatureType::Road,
+        );
+        assert_eq!(score_feature(&road, &tile_data, false), 1);
+        assert_eq!(score_feature(&road, &tile_data, true), 1);
+        let inn_road = lone_feature(
+            &tile_data,
+            TileType::RFRF_02_Inn,
+            AreaType::InnRoad,
+            FeatureType::Road,
+        );
+        assert_eq!(score_feature(&inn_road, &tile_data, false), 0);
+        assert_eq!(score_feature(&inn_road, &tile_data, true), 2);
+        let cathedral = lone_feature(
+            &tile_data,
+            TileType::TTTT_0123_Cathedral,
+            AreaType::CathedralTown,
+            FeatureType::Town,
+        );
+        assert_eq!(score_feature(&cathedral, &tile_data, false), 0);
+        assert_eq!(score_feature(&cathedral, &tile_data, true), 3);
+    }
+
+    #[test]
+    fn tied_players_all_own_the_feature() {
+        let tile_data = create_tiles(&[Expansion::InnsAndCathedrals]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let mut roads = vec![];
+        for n in 0..3 {
+            let tile_idx = nth_tile(&tile_data, TileType::RFRF_02, n);
+            let coords = TileMatrixCoords { x: n as i32, y: 0 };
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                coords,
+            );
+            roads.push(area_of(&tile_data, tile_idx, AreaType::Road));
+        }
+        let feature = get_feature(&gameplay_data, &tile_data, FeatureType::Road, roads[0]);
+        assert!(get_feature_owners(&feature, &gameplay_data, &tile_data).is_empty());
+
+        let meeple = |player| PlacedMeeple { meeple: 0, player };
+        gameplay_data.area_meeples.insert(roads[0], meeple(0));
+        gameplay_data.area_meeples.insert(roads[1], meeple(1));
+        let owners = get_feature_owners(&feature, &gameplay_data, &tile_data);
+        assert_eq!(owners, vec![0, 1]);
+        let mut players = two_players(&[]);
+        award_points(&mut players.players, &owners, 3);
+        assert_eq!(players.players[0].points, 3);
+        assert_eq!(players.players[1].points, 3);
+
+        // A large meeple breaks the tie, and a second meeple ties it again.
+        let large_meeple = PlacedMeeple {
+            meeple: large_meeple_index(1),
+            player: 1,
+        };
+        gameplay_data.area_meeples.insert(roads[1], large_meeple);
+        assert_eq!(
+            get_feature_owners(&feature, &gameplay_data, &tile_data),
+            vec![1]
+        );
+        gameplay_data.area_meeples.insert(roads[2], meeple(0));
+        assert_eq!(
+            get_feature_owners(&feature, &gameplay_data, &tile_data),
+            vec![0, 1]
+        );
+    }
+
+    // Two towns facing each other close into one, a third stays open. Returns the board and
+    // the fields of the three tiles.
+    fn closed_town_board(tile_data: &GameTileData) -> (GameplayData, Vec<TileAreaIndex>) {
+        let mut gameplay_data = GameplayData::new(tile_data, 7);
+        let towns = [
+            (TileDirection::UP, 0, 0),
+            (TileDirection::DOWN, 0, 1),
+            (TileDirection::DOWN, 1, 0),
+        ];
+        let mut farms = vec![];
+        for (n, (dir, x, y)) in towns.into_iter().enumerate() {
+            let tile_idx = nth_tile(tile_data, TileType::FFFT, n);
+            place_tile(
+                &mut gameplay_data,
+                tile_data,
+                tile_idx,
+                dir,
+                TileMatrixCoords { x, y },
+            );
+            farms.push(area_of(tile_data, tile_idx, AreaType::Farm));
+        }
+        return (gameplay_data, farms);
+    }
+
+    #[test]
+    fn farmers_score_completed_towns() {
+        let tile_data = create_tiles(&[]);
+        let (mut gameplay_data, farms) = closed_town_board(&tile_data);
+        let mut players = two_players(&[]);
+        score_farms(&gameplay_data, &tile_data, &mut players.players);
+        assert_eq!(players.players[0].points, 0);
+
+        // The field south of the closed town also runs along the open one.
+        let field = get_feature(&gameplay_data, &tile_data, FeatureType::Farm, farms[0]);
+        assert!(field.areas.contains(&farms[2]));
+        assert_eq!(
+            get_supplied_towns(&field, &gameplay_data, &tile_data).len(),
+            1
+        );
+        gameplay_data.area_meeples.insert(
+            farms[0],
+            PlacedMeeple {
+                meeple: 0,
+                player: 0,
+            },
+        );
+        gameplay_data.area_meeples.insert(
+            farms[1],
+            PlacedMeeple {
+                meeple: 0,
+                player: 1,
+            },
+        );
+        score_farms(&gameplay_data, &tile_data, &mut players.players);
+        assert_eq!(players.players[0].points, 3);
+        assert_eq!(players.players[1].points, 3);
+    }
+
+    #[test]
+    fn goods_majorities_score_ten_each() {
+        let mut players = two_players(&[Expansion::TradersAndBuilders]);
+        players.players[0].goods = [2, 1, 0];
+        players.players[1].goods = [2, 0, 0];
+        score_goods(&mut players.players);
+        // Both lead on wine, only the first on grain, and nobody has cloth.
+        assert_eq!(players.players[0].points, 20);
+        assert_eq!(players.players[1].points, 10);
+    }
+
+    #[test]
+    fn pig_adds_a_point_per_town() {
+        let tile_data = create_tiles(&[Expansion::TradersAndBuilders]);
+        let (mut gameplay_data, farms) = closed_town_board(&tile_data);
+        let mut players = two_players(&[Expansion::TradersAndBuilders]);
+        let farmer = PlacedMeeple {
+            meeple: 0,
+            player: 0,
+        };
+        gameplay_data.area_meeples.insert(farms[0], farmer);
+        let pig = PlacedMeeple {
+            meeple: pig_index(0),
+            player: 0,
+        };
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farms[2],
+            pig
+        ));
+        // Somebody else's pig can't join the field.
+        let other_pig = PlacedMeeple {
+            meeple: pig_index(1),
+            player: 1,
+        };
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farms[2],
+            other_pig
+        ));
+        gameplay_data.area_meeples.insert(farms[2], pig);
+        score_farms(&gameplay_data, &tile_data, &mut players.players);
+        assert_eq!(players.players[0].points, 4);
+    }
+
+    #[test]
+    fn fairy_adds_three_points() {
+        let tile_data = create_tiles(&[Expansion::PrincessAndDragon]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        let origin = TileMatrixCoords { x: 0, y: 0 };
+        place_tile(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            origin,
+        );
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        let feature = get_feature(&gameplay_data, &tile_data, FeatureType::Road, road_area);
+        let mut players = two_players(&[]);
+        gameplay_data.area_meeples.insert(
+            road_area,
+            PlacedMeeple {
+                meeple: 0,
+                player: 1,
+            },
+        );
+        score_fairy_feature(&feature, &gameplay_data, &mut players.players);
+        assert_eq!(players.players[1].points, 0);
+        gameplay_data.fairy = Some(road_area);
+        score_fairy_feature(&feature, &gameplay_data, &mut players.players);
+        assert_eq!(players.players[1].points, 3);
+    }
+
+    #[test]
+    fn mayor_counts_the_pennants() {
+        let tile_data = create_tiles(&[Expansion::AbbeyAndMayor]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let pennant_town = nth_tile(&tile_data, TileType::PFPF_02, 0);
+        let town = nth_tile(&tile_data, TileType::TFTF_02, 0);
+        for (n, tile_idx) in [pennant_town, town].into_iter().enumerate() {
+            let coords = TileMatrixCoords { x: n as i32, y: 0 };
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                coords,
+            );
+        }
+        let mayor_area = area_of(&tile_data, pennant_town, AreaType::PennantTown);
+        let knight_area = area_of(&tile_data, town, AreaType::Town);
+        let feature = get_feature(&gameplay_data, &tile_data, FeatureType::Town, knight_area);
+        assert!(feature.areas.contains(&mayor_area));
+        let mayor = PlacedMeeple {
+            meeple: mayor_index(0),
+            player: 0,
+        };
+        gameplay_data.area_meeples.insert(mayor_area, mayor);
+        let knight = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        gameplay_data.area_meeples.insert(knight_area, knight);
+        assert_eq!(
+            get_feature_owners(&feature, &gameplay_data, &tile_data),
+            vec![0, 1]
+        );
+
+        // Without pennants the mayor is worth nothing.
+        let lone_town = nth_tile(&tile_data, TileType::FFFT, 0);
+        let coords = TileMatrixCoords { x: 5, y: 5 };
+        place_tile(
+            &mut gameplay_data,
+            &tile_data,
+            lone_town,
+            TileDirection::UP,
+            coords,
+        );
+        let lone_town_area = area_of(&tile_data, lone_town, AreaType::Town);
+        gameplay_data.area_meeples.insert(lone_town_area, mayor);
+        let feature = get_feature(
+            &gameplay_data,
+            &tile_data,
+            FeatureType::Town,
+            lone_town_area,
+        );
+        assert!(get_feature_owners(&feature, &gameplay_data, &tile_data).is_empty());
+    }
+
+    #[test]
+    fn barn_scores_the_field_it_joins() {
+        let tile_data = create_tiles(&[Expansion::AbbeyAndMayor]);
+        let (mut gameplay_data, farms) = closed_town_board(&tile_data);
+        let mut players = two_players(&[Expansion::AbbeyAndMayor]);
+        let farmer = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        let num_meeples = players.players[1].meeples.len();
+        place_meeple(&mut gameplay_data, &mut players, farms[2], farmer);
+        let barn = PlacedMeeple {
+            meeple: barn_index(0),
+            player: 0,
+        };
+        assert!(figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farms[0],
+            barn
+        ));
+        place_meeple(&mut gameplay_data, &mut players, farms[0], barn);
+        // The farmers already there score now and go home.
+        let returned = score_barn_placement(&mut gameplay_data, &tile_data, &mut players, farms[0]);
+        assert_eq!(returned.len(), 1);
+        assert_eq!(players.players[1].points, 3);
+        assert_eq!(players.players[1].meeples.len(), num_meeples);
+        assert!(gameplay_data.area_meeples.contains_key(&farms[0]));
+        let other_barn = PlacedMeeple {
+            meeple: barn_index(1),
+            player: 1,
+        };
+        assert!(!figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            farms[2],
+            other_barn
+        ));
+
+        // At the end the barn gets 4 a town, farmers who came in later only 1.
+        place_meeple(&mut gameplay_data, &mut players, farms[2], farmer);
+        score_farms(&gameplay_data, &tile_data, &mut players.players);
+        assert_eq!(players.players[0].points, 4);
+        assert_eq!(players.players[1].points, 4);
+    }
+
+    #[test]
+    fn hill_breaks_a_tie() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let hill = nth_tile(&tile_data, TileType::RFRF_02_Hill, 0);
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        for (n, tile_idx) in [hill, road].into_iter().enumerate() {
+            let coords = TileMatrixCoords { x: n as i32, y: 0 };
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                coords,
+            );
+        }
+        let hill_area = area_of(&tile_data, hill, AreaType::Road);
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        gameplay_data.area_meeples.insert(
+            hill_area,
+            PlacedMeeple {
+                meeple: NUM_MEEPLES,
+                player: 1,
+            },
+        );
+        gameplay_data.area_meeples.insert(
+            road_area,
+            PlacedMeeple {
+                meeple: 0,
+                player: 0,
+            },
+        );
+        let feature = get_feature(&gameplay_data, &tile_data, FeatureType::Road, road_area);
+        assert_eq!(
+            get_feature_owners(&feature, &gameplay_data, &tile_data),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn vineyards_add_to_a_completed_cloister() {
+        let tile_data = create_tiles(&[Expansion::HillsAndSheep]);
+        let mut gameplay_data = GameplayData::new(&tile_data, 7);
+        let cloister = nth_tile(&tile_data, TileType::FFFF_C, 0);
+        let origin = TileMatrixCoords { x: 0, y: 0 };
+        place_tile(
+            &mut gameplay_data,
+            &tile_data,
+            cloister,
+            TileDirection::UP,
+            origin,
+        );
+        let mut neighbors = vec![
+            nth_tile(&tile_data, TileType::FFFF_Vineyard, 0),
+            nth_tile(&tile_data, TileType::RFRF_02_Vineyard, 0),
+        ];
+        neighbors.extend((0..6).map(|n| nth_tile(&tile_data, TileType::RFRF_02, n)));
+        for (tile_idx, offset) in neighbors.into_iter().zip(SURROUNDING_COORDS) {
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                offset,
+            );
+        }
+        let completed = find_completed_features(&gameplay_data, &tile_data, cloister);
+        let feature = completed
+            .iter()
+            .find(|f| f.feature_type == FeatureType::Cloister)
+            .unwrap();
+        assert_eq!(score_feature(feature, &tile_data, true), 9 + 2 * 3);
+        // Unfinished, the vineyards are worth nothing.
+        assert_eq!(score_feature(feature, &tile_data, false), 9);
+    }
+
+    #[test]
+    fn castle_scores_a_feature_in_its_fief() {
+        let tile_data = create_tiles(&[Expansion::BridgesCastlesBazaars]);
+        let (mut gameplay_data, _) = closed_town_board(&tile_data);
+        let mut players = two_players(&[Expansion::BridgesCastlesBazaars]);
+        let town = nth_tile(&tile_data, TileType::FFFT, 0);
+        let town_area = area_of(&tile_data, town, AreaType::Town);
+        let knight = PlacedMeeple {
+            meeple: NUM_MEEPLES,
+            player: 1,
+        };
+        place_meeple(&mut gameplay_data, &mut players, town_area, knight);
+        let town = get_feature(&gameplay_data, &tile_data, FeatureType::Town, town_area);
+        assert!(feature_can_become_castle(&gameplay_data, &town));
+        build_castle(&mut gameplay_data, &town);
+        assert!(!gameplay_data.area_meeples.contains_key(&town_area));
+
+        // Cloisters with the castle's tiles among their neighbours don't count, only ones
+        // standing in the fief.
+        let cloister = nth_tile(&tile_data, TileType::FFFF_C, 0);
+        let cloister_coords = TileMatrixCoords { x: -2, y: 0 };
+        let next_to_castle = nth_tile(&tile_data, TileType::FFFF_C, 1);
+        for (tile_idx, x) in [(cloister, -2), (next_to_castle, -1)] {
+            let coords = TileMatrixCoords { x, y: 0 };
+            place_tile(
+                &mut gameplay_data,
+                &tile_data,
+                tile_idx,
+                TileDirection::UP,
+                coords,
+            );
+        }
+        let cloister_feature = Feature {
+            feature_type: FeatureType::Cloister,
+            tiles: get_cloister_tiles(&gameplay_data, cloister_coords),
+            areas: vec![area_of(&tile_data, cloister, AreaType::Cloister)],
+        };
+        assert!(cloister_feature.tiles.contains(&next_to_castle));
+        let returned = score_castles(
+            &[cloister_feature],
+            &mut gameplay_data,
+            &tile_data,
+            &mut players,
+        );
+        assert!(returned.is_empty());
+        assert_eq!(gameplay_data.castles.len(), 1);
+
+        // The fief takes in the diagonals.
+        let road = nth_tile(&tile_data, TileType::RFRF_02, 0);
+        let diagonal = TileMatrixCoords { x: -1, y: -1 };
+        place_tile(
+            &mut gameplay_data,
+            &tile_data,
+            road,
+            TileDirection::UP,
+            diagonal,
+        );
+        let road_area = area_of(&tile_data, road, AreaType::Road);
+        let road = get_feature(&gameplay_data, &tile_data, FeatureType::Road, road_area);
+        let returned = score_castles(&[road], &mut gameplay_data, &tile_data, &mut players);
+        assert_eq!(returned.len(), 1);
+        assert!(gameplay_data.castles.is_empty());
+        assert_eq!(players.players[1].points, 1);
+        assert_eq!(players.players[1].meeples.len(), NUM_MEEPLES);
+    }
+}
@@ -0,0 +1,1353 @@
+use crate::players::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AreaType {
+    Unspecified,
+    Farm,
+    Road,
+    // A road running past an inn.
+    InnRoad,
+    EndRoad,
+    RoadStopMarker,
+    Town,
+    PennantTown,
+    CathedralTown,
+    Cloister,
+    // Only an abbot may stand in a garden. It scores like a cloister.
+    Garden,
+    Water,
+    // Around an abbey. Closes whatever it touches without joining it.
+    AbbeyWall,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FeatureType {
+    Road,
+    Town,
+    Cloister,
+    Farm,
+}
+
+// Kind of scorable feature an area is part of, if any.
+pub fn area_type_to_feature_type(area_type: AreaType) -> Option<FeatureType> {
+    match area_type {
+        AreaType::Road | AreaType::InnRoad | AreaType::EndRoad => Some(FeatureType::Road),
+        AreaType::Town | AreaType::PennantTown | AreaType::CathedralTown => Some(FeatureType::Town),
+        AreaType::Cloister | AreaType::Garden => Some(FeatureType::Cloister),
+        AreaType::Farm => Some(FeatureType::Farm),
+        _ => None,
+    }
+}
+
+// Whether a meeple may stand on an area of this type.
+pub fn area_type_is_claimable(area_type: AreaType) -> bool {
+    return !matches!(
+        area_type,
+        AreaType::RoadStopMarker | AreaType::Water | AreaType::AbbeyWall | AreaType::Unspecified
+    );
+}
+
+// Whether two areas can meet across the shared edge of two adjacent tiles.
+pub fn area_types_connect(a: AreaType, b: AreaType) -> bool {
+    return matches!(
+        (a, b),
+        (
+            AreaType::Road | AreaType::InnRoad | AreaType::EndRoad,
+            AreaType::Road | AreaType::InnRoad | AreaType::EndRoad
+        ) | (
+            AreaType::Town | AreaType::PennantTown | AreaType::CathedralTown,
+            AreaType::Town | AreaType::PennantTown | AreaType::CathedralTown
+        ) | (AreaType::Farm, AreaType::Farm)
+            | (AreaType::Water, AreaType::Water)
+    );
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Goods {
+    Wine,
+    Grain,
+    Cloth,
+}
+
+pub const NUM_GOODS: usize = 3;
+pub const GOODS: [Goods; NUM_GOODS] = [Goods::Wine, Goods::Grain, Goods::Cloth];
+
+// Starting from right edge. Each group after first 4 letters is connected
+// road or town edges, and last optional groups is if cloister is present.
+// https://en.wikipedia.org/wiki/Carcassonne_(board_game)#Tiles
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TileType {
+    Unspecified,
+    // base game
+    RFRF_02,
+    FRRF_12,
+    RRRF,
+    RRRR,
+    FFFF_C,
+    FRFF_C,
+    //
+    FFFT,
+    RFRT_02,
+    RRFT_01,
+    FRRT_12,
+    RRRT,
+    //
+    FTFT,
+    TFFT,
+    TFTF_02,
+    PFPF_02,
+    TFFT_03,
+    PFFP_03,
+    TRRT_03_12,
+    PRRP_03_12,
+    //
+    TFTT_013,
+    PFPP_013,
+    TRTT_013,
+    PRPP_013,
+    //
+    PPPP_0123,
+    // waters
+    FWFF,
+    WFWF_02,
+    FWWF_12,
+    WRWF_02_C,
+    WRWR_02_13,
+    RWWR_03_12,
+    //
+    WRWT_02,
+    //
+    WTWT_02,
+    TWWT_03,
+    // inns & cathedrals
+    RFRF_02_Inn,
+    FRRF_12_Inn,
+    RFRT_02_Inn,
+    RRFT_01_Inn,
+    TRRT_03_12_Inn,
+    PRRP_03_12_Inn,
+    //
+    TTTT_0123_Cathedral,
+    // traders & builders
+    FFFT_Wine,
+    TFFT_03_Wine,
+    TRRT_03_12_Wine,
+    FFFT_Grain,
+    TFTF_02_Grain,
+    TFTT_013_Grain,
+    FTFT_Cloth,
+    TFFT_03_Cloth,
+    TRTT_013_Cloth,
+    // princess & dragon
+    FFFF_Volcano,
+    FFFT_Volcano,
+    RFRF_02_Dragon,
+    FRRF_12_Dragon,
+    TFFT_03_Dragon,
+    FTFT_Dragon,
+    RFRF_02_Portal,
+    FRRF_12_Portal,
+    FFFT_Portal,
+    FFFT_Princess,
+    TFFT_03_Princess,
+    TFTT_013_Princess,
+    // abbey & mayor
+    Abbey,
+    // tower
+    RFRF_02_Tower,
+    FRRF_12_Tower,
+    RRRF_Tower,
+    FFFT_Tower,
+    TFFT_03_Tower,
+    TFTF_02_Tower,
+    // hills & sheep
+    RFRF_02_Hill,
+    FRRF_12_Hill,
+    FFFT_Hill,
+    TFFT_03_Hill,
+    FFFF_Vineyard,
+    RFRF_02_Vineyard,
+    FRRF_12_Vineyard,
+    // bridges, castles & bazaars
+    RFRF_02_Bazaar,
+    FRRF_12_Bazaar,
+    TFFT_03_Bazaar,
+    // gardens
+    FFFF_Garden,
+    RFRF_02_Garden,
+    FFFT_Garden,
+}
+
+// Three per side, clockwise from the top of the right side: 0-2 right, 3-5 bottom, 6-8 left,
+// 9-11 top.
+pub type EdgeNumber = usize;
+pub type TileAreaIndex = usize;
+pub type TileIndex = usize;
+pub const PLACEHOLDER_TILE_OFFSET: TileIndex = 100000;
+
+#[derive(Debug, Clone)]
+pub struct TileArea {
+    pub area_type: AreaType,
+    pub self_idx: TileAreaIndex,
+    pub tile_idx: TileIndex,
+    pub edges: Vec<EdgeNumber>,
+    // To areas in same tile, only used for meadow-city interactions.
+    pub connected_areas: Vec<TileAreaIndex>,
+    // Goods symbol printed in a town.
+    pub goods: Option<Goods>,
+}
+
+fn create_area(area_type: AreaType, edges: Vec<EdgeNumber>) -> TileArea {
+    return TileArea {
+        area_type,
+        self_idx: 0,
+        tile_idx: 0,
+        edges,
+        connected_areas: vec![],
+        goods: None,
+    };
+}
+
+// Bridges add a road to a tile that is already in the game. It shares its edges with the
+// field it crosses and, coming last, is the area get_rotated_edge_areas finds on them.
+pub fn add_bridge_area(
+    tile_data: &mut GameTileData,
+    tile_idx: TileIndex,
+    edges: Vec<EdgeNumber>,
+) -> TileAreaIndex {
+    let area_idx = tile_data.all_areas.len();
+    let mut area = create_area(AreaType::Road, edges);
+    area.self_idx = area_idx;
+    area.tile_idx = tile_idx;
+    tile_data.all_areas.push(area);
+    tile_data.all_tiles[tile_idx].areas.push(area_idx);
+    return area_idx;
+}
+
+fn create_goods_area(area_type: AreaType, edges: Vec<EdgeNumber>, goods: Goods) -> TileArea {
+    let mut area = create_area(area_type, edges);
+    area.goods = Some(goods);
+    return area;
+}
+
+fn fill_area_idxs(areas: &mut Vec<TileArea>, mut offset: TileAreaIndex) -> Vec<TileAreaIndex> {
+    let mut all_edges: Vec<EdgeNumber> = vec![];
+    for a in &mut *areas {
+        all_edges.extend(&a.edges);
+    }
+    all_edges.sort();
+    if all_edges != vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11] {
+        panic!(
+            "all_edges did not contain all edges of tile. areas: {:?}",
+            areas
+        );
+    }
+
+    let mut idxs: Vec<TileAreaIndex> = vec![];
+    for area in areas {
+        area.self_idx = offset;
+        idxs.push(offset);
+        offset += 1;
+    }
+    return idxs;
+}
+
+fn make_tile_area_connections(
+    areas: &mut [TileArea],
+    conns: Vec<[TileAreaIndex; 2]>,
+    offset: TileAreaIndex,
+) {
+    for conn in conns {
+        areas[offset + conn[0]]
+            .connected_areas
+            .push(offset + conn[1]);
+        areas[offset + conn[1]]
+            .connected_areas
+            .push(offset + conn[0]);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub areas: Vec<TileAreaIndex>,
+    pub tile_type: TileType,
+}
+
+fn get_tile(tile_type: TileType, all_areas: &mut Vec<TileArea>) -> Tile {
+    let offs: TileAreaIndex = all_areas.len();
+
+    let mut areas: Vec<TileArea>;
+    // Farms touching towns, as relative area indexes.
+    let mut conns: Vec<[TileAreaIndex; 2]> = vec![];
+    match tile_type {
+        TileType::RFRF_02 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        //
+        TileType::RRRF => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::EndRoad, vec![1]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::EndRoad, vec![7]),
+                create_area(AreaType::RoadStopMarker, vec![]),
+            ];
+        }
+        TileType::RRRR => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 11]),
+                create_area(AreaType::EndRoad, vec![1]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::EndRoad, vec![7]),
+                create_area(AreaType::Farm, vec![8, 9]),
+                create_area(AreaType::EndRoad, vec![10]),
+                create_area(AreaType::RoadStopMarker, vec![]),
+            ];
+        }
+        TileType::FFFF_C => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+                //
+                create_area(AreaType::Cloister, vec![]),
+            ];
+        }
+        TileType::FRFF_C => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 5, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::EndRoad, vec![4]),
+                //
+                create_area(AreaType::Cloister, vec![]),
+            ];
+        }
+        TileType::FFFT => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::RFRT_02 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 3]];
+        }
+        TileType::RRFT_01 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 5, 6, 7, 8]),
+                create_area(AreaType::Road, vec![1, 4]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 3]];
+        }
+        TileType::FRRT_12 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 3]];
+        }
+        TileType::RRRT => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8]),
+                create_area(AreaType::EndRoad, vec![1]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::EndRoad, vec![7]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+                create_area(AreaType::RoadStopMarker, vec![]),
+            ];
+            conns = vec![[0, 6]];
+        }
+        TileType::FTFT => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Town, vec![3, 4, 5]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1], [0, 2]];
+        }
+        TileType::TFFT => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[1, 0], [1, 2]];
+        }
+        TileType::TFTF_02 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+                create_area(AreaType::Farm, vec![9, 10, 11]),
+            ];
+            conns = vec![[1, 0], [2, 0]];
+        }
+        TileType::PFPF_02 => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+                create_area(AreaType::Farm, vec![9, 10, 11]),
+            ];
+            conns = vec![[1, 0], [2, 0]];
+        }
+        TileType::TFFT_03 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::PFFP_03 => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TRRT_03_12 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::PRRP_03_12 => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TFTT_013 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::PFPP_013 => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TRTT_013 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5]),
+            ];
+            conns = vec![[1, 0], [3, 0]];
+        }
+        TileType::PRPP_013 => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5]),
+            ];
+            conns = vec![[1, 0], [3, 0]];
+        }
+        TileType::PPPP_0123 => {
+            areas = vec![create_area(
+                AreaType::PennantTown,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            )];
+        }
+        // waters
+        TileType::FWFF => {
+            // Spring or lake, depending on which end of the river it is.
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 5, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Water, vec![4]),
+            ];
+        }
+        TileType::WFWF_02 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Water, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ];
+        }
+        TileType::FWWF_12 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Water, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::WRWF_02_C => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Water, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 5, 6]),
+                create_area(AreaType::EndRoad, vec![4]),
+                //
+                create_area(AreaType::Cloister, vec![]),
+            ];
+        }
+        TileType::WRWR_02_13 => {
+            // The road crosses the river on a bridge.
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 11]),
+                create_area(AreaType::Water, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::Road, vec![4, 10]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::Farm, vec![8, 9]),
+            ];
+        }
+        TileType::RWWR_03_12 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 11]),
+                create_area(AreaType::Road, vec![1, 10]),
+                create_area(AreaType::Farm, vec![2, 3, 8, 9]),
+                create_area(AreaType::Water, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        //
+        TileType::WRWT_02 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8]),
+                create_area(AreaType::Water, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 5]];
+        }
+        //
+        TileType::WTWT_02 => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8]),
+                create_area(AreaType::Water, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 6]),
+                create_area(AreaType::Town, vec![3, 4, 5]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 4], [2, 3]];
+        }
+        TileType::TWWT_03 => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::Water, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        // inns & cathedrals
+        TileType::RFRF_02_Inn => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::InnRoad, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ];
+        }
+        TileType::FRRF_12_Inn => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::InnRoad, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::RFRT_02_Inn => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8]),
+                create_area(AreaType::InnRoad, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 3]];
+        }
+        TileType::RRFT_01_Inn => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 5, 6, 7, 8]),
+                create_area(AreaType::InnRoad, vec![1, 4]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 3]];
+        }
+        TileType::TRRT_03_12_Inn => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::InnRoad, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::PRRP_03_12_Inn => {
+            areas = vec![
+                create_area(AreaType::PennantTown, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::InnRoad, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        //
+        TileType::TTTT_0123_Cathedral => {
+            areas = vec![create_area(
+                AreaType::CathedralTown,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            )];
+        }
+        // traders & builders
+        TileType::FFFT_Wine => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_goods_area(AreaType::Town, vec![9, 10, 11], Goods::Wine),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::TFFT_03_Wine => {
+            areas = vec![
+                create_goods_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11], Goods::Wine),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TRRT_03_12_Wine => {
+            areas = vec![
+                create_goods_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11], Goods::Wine),
+                create_area(AreaType::Farm, vec![3, 8]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::FFFT_Grain => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_goods_area(AreaType::Town, vec![9, 10, 11], Goods::Grain),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::TFTF_02_Grain => {
+            areas = vec![
+                create_goods_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8], Goods::Grain),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+                create_area(AreaType::Farm, vec![9, 10, 11]),
+            ];
+            conns = vec![[1, 0], [2, 0]];
+        }
+        TileType::TFTT_013_Grain => {
+            areas = vec![
+                create_goods_area(
+                    AreaType::Town,
+                    vec![0, 1, 2, 6, 7, 8, 9, 10, 11],
+                    Goods::Grain,
+                ),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::FTFT_Cloth => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Town, vec![3, 4, 5]),
+                create_goods_area(AreaType::Town, vec![9, 10, 11], Goods::Cloth),
+            ];
+            conns = vec![[0, 1], [0, 2]];
+        }
+        TileType::TFFT_03_Cloth => {
+            areas = vec![
+                create_goods_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11], Goods::Cloth),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TRTT_013_Cloth => {
+            areas = vec![
+                create_goods_area(
+                    AreaType::Town,
+                    vec![0, 1, 2, 6, 7, 8, 9, 10, 11],
+                    Goods::Cloth,
+                ),
+                create_area(AreaType::Farm, vec![3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5]),
+            ];
+            conns = vec![[1, 0], [3, 0]];
+        }
+        // princess & dragon
+        TileType::FFFF_Volcano => {
+            areas = vec![create_area(
+                AreaType::Farm,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            )];
+        }
+        TileType::FFFT_Volcano => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::RFRF_02_Dragon => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Dragon => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::TFFT_03_Dragon => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::FTFT_Dragon => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Town, vec![3, 4, 5]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1], [0, 2]];
+        }
+        TileType::RFRF_02_Portal => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Portal => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::FFFT_Portal => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::FFFT_Princess => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::TFFT_03_Princess => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TFTT_013_Princess => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        // abbey & mayor
+        TileType::Abbey => {
+            areas = vec![
+                create_area(
+                    AreaType::AbbeyWall,
+                    vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+                ),
+                create_area(AreaType::Cloister, vec![]),
+            ];
+        }
+        // tower
+        TileType::RFRF_02_Tower => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Tower => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::RRRF_Tower => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::EndRoad, vec![1]),
+                create_area(AreaType::Farm, vec![2, 3]),
+                create_area(AreaType::EndRoad, vec![4]),
+                create_area(AreaType::Farm, vec![5, 6]),
+                create_area(AreaType::EndRoad, vec![7]),
+                create_area(AreaType::RoadStopMarker, vec![]),
+            ];
+        }
+        TileType::FFFT_Tower => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::TFFT_03_Tower => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::TFTF_02_Tower => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 6, 7, 8]),
+                create_area(AreaType::Farm, vec![3, 4, 5]),
+                create_area(AreaType::Farm, vec![9, 10, 11]),
+            ];
+            conns = vec![[1, 0], [2, 0]];
+        }
+        // hills & sheep
+        TileType::RFRF_02_Hill => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Hill => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::FFFT_Hill => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::TFFT_03_Hill => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        TileType::FFFF_Vineyard => {
+            areas = vec![create_area(
+                AreaType::Farm,
+                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            )];
+        }
+        TileType::RFRF_02_Vineyard => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Vineyard => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        // bridges, castles & bazaars
+        TileType::RFRF_02_Bazaar => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+            ]
+        }
+        TileType::FRRF_12_Bazaar => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![4, 7]),
+                create_area(AreaType::Farm, vec![5, 6]),
+            ];
+        }
+        TileType::TFFT_03_Bazaar => {
+            areas = vec![
+                create_area(AreaType::Town, vec![0, 1, 2, 9, 10, 11]),
+                create_area(AreaType::Farm, vec![3, 4, 5, 6, 7, 8]),
+            ];
+            conns = vec![[1, 0]];
+        }
+        // gardens
+        TileType::FFFF_Garden => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+                //
+                create_area(AreaType::Garden, vec![]),
+            ];
+        }
+        TileType::RFRF_02_Garden => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 8, 9, 10, 11]),
+                create_area(AreaType::Road, vec![1, 7]),
+                create_area(AreaType::Farm, vec![2, 3, 4, 5, 6]),
+                //
+                create_area(AreaType::Garden, vec![]),
+            ];
+        }
+        TileType::FFFT_Garden => {
+            areas = vec![
+                create_area(AreaType::Farm, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]),
+                create_area(AreaType::Town, vec![9, 10, 11]),
+                //
+                create_area(AreaType::Garden, vec![]),
+            ];
+            conns = vec![[0, 1]];
+        }
+        TileType::Unspecified => todo!(),
+    }
+    let idxs: Vec<TileAreaIndex> = fill_area_idxs(&mut areas, offs);
+
+    all_areas.append(&mut areas);
+    make_tile_area_connections(all_areas, conns, offs);
+    return Tile {
+        areas: idxs,
+        tile_type,
+    };
+}
+
+#[derive(Clone, Debug)]
+pub struct GameTileData {
+    pub all_areas: Vec<TileArea>,
+    pub all_tiles: Vec<Tile>,
+}
+
+impl Default for GameTileData {
+    fn default() -> Self {
+        return create_tiles(&[]);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expansion {
+    River,
+    InnsAndCathedrals,
+    TradersAndBuilders,
+    PrincessAndDragon,
+    AbbeyAndMayor,
+    Tower,
+    HillsAndSheep,
+    BridgesCastlesBazaars,
+    GardensAndAbbot,
+}
+
+pub fn expansion_tiles(expansion: Expansion) -> Vec<(TileType, usize)> {
+    match expansion {
+        Expansion::River => vec![
+            (TileType::FWFF, 2),
+            (TileType::WFWF_02, 2),
+            (TileType::FWWF_12, 2),
+            (TileType::WRWF_02_C, 1),
+            (TileType::WRWR_02_13, 1),
+            (TileType::RWWR_03_12, 1),
+            (TileType::WRWT_02, 1),
+            (TileType::WTWT_02, 1),
+            (TileType::TWWT_03, 1),
+        ],
+        Expansion::InnsAndCathedrals => vec![
+            (TileType::RFRF_02_Inn, 1),
+            (TileType::FRRF_12_Inn, 1),
+            (TileType::RFRT_02_Inn, 1),
+            (TileType::RRFT_01_Inn, 1),
+            (TileType::TRRT_03_12_Inn, 1),
+            (TileType::PRRP_03_12_Inn, 1),
+            (TileType::TTTT_0123_Cathedral, 2),
+        ],
+        Expansion::TradersAndBuilders => vec![
+            (TileType::FFFT_Wine, 1),
+            (TileType::TFFT_03_Wine, 1),
+            (TileType::TRRT_03_12_Wine, 1),
+            (TileType::FFFT_Grain, 1),
+            (TileType::TFTF_02_Grain, 1),
+            (TileType::TFTT_013_Grain, 1),
+            (TileType::FTFT_Cloth, 1),
+            (TileType::TFFT_03_Cloth, 1),
+            (TileType::TRTT_013_Cloth, 1),
+        ],
+        Expansion::PrincessAndDragon => vec![
+            (TileType::FFFF_Volcano, 2),
+            (TileType::FFFT_Volcano, 1),
+            (TileType::RFRF_02_Dragon, 1),
+            (TileType::FRRF_12_Dragon, 1),
+            (TileType::TFFT_03_Dragon, 1),
+            (TileType::FTFT_Dragon, 1),
+            (TileType::RFRF_02_Portal, 1),
+            (TileType::FRRF_12_Portal, 1),
+            (TileType::FFFT_Portal, 1),
+            (TileType::FFFT_Princess, 1),
+            (TileType::TFFT_03_Princess, 1),
+            (TileType::TFTT_013_Princess, 1),
+        ],
+        // One abbey for every seat, counting the extra color of Inns & Cathedrals. They are
+        // handed out, not drawn.
+        Expansion::AbbeyAndMayor => vec![(TileType::Abbey, MAX_PLAYERS + 1)],
+        Expansion::Tower => vec![
+            (TileType::RFRF_02_Tower, 3),
+            (TileType::FRRF_12_Tower, 3),
+            (TileType::RRRF_Tower, 2),
+            (TileType::FFFT_Tower, 3),
+            (TileType::TFFT_03_Tower, 3),
+            (TileType::TFTF_02_Tower, 2),
+        ],
+        Expansion::HillsAndSheep => vec![
+            (TileType::RFRF_02_Hill, 2),
+            (TileType::FRRF_12_Hill, 2),
+            (TileType::FFFT_Hill, 2),
+            (TileType::TFFT_03_Hill, 2),
+            (TileType::FFFF_Vineyard, 1),
+            (TileType::RFRF_02_Vineyard, 1),
+            (TileType::FRRF_12_Vineyard, 1),
+        ],
+        Expansion::BridgesCastlesBazaars => vec![
+            (TileType::RFRF_02_Bazaar, 2),
+            (TileType::FRRF_12_Bazaar, 2),
+            (TileType::TFFT_03_Bazaar, 2),
+        ],
+        Expansion::GardensAndAbbot => vec![
+            (TileType::FFFF_Garden, 2),
+            (TileType::RFRF_02_Garden, 2),
+            (TileType::FFFT_Garden, 2),
+        ],
+    }
+}
+
+// River tiles come out of their own stack before the rest.
+pub fn tile_type_is_river(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::FWFF
+            | TileType::WFWF_02
+            | TileType::FWWF_12
+            | TileType::WRWF_02_C
+            | TileType::WRWR_02_13
+            | TileType::RWWR_03_12
+            | TileType::WRWT_02
+            | TileType::WTWT_02
+            | TileType::TWWT_03
+    );
+}
+
+// The dragon comes out on a volcano.
+pub fn tile_type_has_volcano(tile_type: TileType) -> bool {
+    return matches!(tile_type, TileType::FFFF_Volcano | TileType::FFFT_Volcano);
+}
+
+// Placing one of these makes the dragon move.
+pub fn tile_type_has_dragon(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::RFRF_02_Dragon
+            | TileType::FRRF_12_Dragon
+            | TileType::TFFT_03_Dragon
+            | TileType::FTFT_Dragon
+    );
+}
+
+// Placing one of these lets the meeple go on any unfinished feature.
+pub fn tile_type_has_portal(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::RFRF_02_Portal | TileType::FRRF_12_Portal | TileType::FFFT_Portal
+    );
+}
+
+// The princess sits in the tile's town.
+pub fn tile_type_has_princess(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::FFFT_Princess | TileType::TFFT_03_Princess | TileType::TFTT_013_Princess
+    );
+}
+
+// Goes in a hole surrounded on all four sides, whatever the sides are.
+pub fn tile_type_is_abbey(tile_type: TileType) -> bool {
+    return tile_type == TileType::Abbey;
+}
+
+// Base game tiles plus those of every expansion played with.
+pub fn create_tiles(expansions: &[Expansion]) -> GameTileData {
+    let mut game_tiles = GameTileData {
+        all_areas: vec![],
+        all_tiles: vec![],
+    };
+
+    let mut tiles_and_qty: Vec<(TileType, usize)> = vec![
+        (TileType::RFRF_02, 8),
+        (TileType::FRRF_12, 9),
+        (TileType::RRRF, 4),
+        (TileType::RRRR, 1),
+        (TileType::FFFF_C, 4),
+        (TileType::FRFF_C, 2),
+        (TileType::FFFT, 5),
+        (TileType::RFRT_02, 4),
+        (TileType::RRFT_01, 3),
+        (TileType::FRRT_12, 3),
+        (TileType::RRRT, 3),
+        (TileType::FTFT, 3),
+        (TileType::TFFT, 2),
+        (TileType::TFTF_02, 1),
+        (TileType::PFPF_02, 2),
+        (TileType::TFFT_03, 3),
+        (TileType::PFFP_03, 2),
+        (TileType::TRRT_03_12, 3),
+        (TileType::PRRP_03_12, 2),
+        (TileType::TFTT_013, 3),
+        (TileType::PFPP_013, 1),
+        (TileType::TRTT_013, 1),
+        (TileType::PRPP_013, 2),
+        (TileType::PPPP_0123, 1),
+    ];
+    for expansion in expansions {
+        tiles_and_qty.extend(expansion_tiles(*expansion));
+    }
+
+    for (tile_type, qty) in tiles_and_qty {
+        for _i in 0..qty {
+            let tile = get_tile(tile_type, &mut game_tiles.all_areas);
+            let tile_idx = game_tiles.all_tiles.len();
+            for area_idx in &tile.areas {
+                game_tiles.all_areas[*area_idx].tile_idx = tile_idx;
+            }
+            game_tiles.all_tiles.push(tile);
+        }
+    }
+
+    return game_tiles;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileDirection {
+    UP,
+    RIGHT,
+    DOWN,
+    LEFT,
+    NONE,
+}
+
+// Every way a tile can be turned.
+pub const TILE_DIRECTIONS: [TileDirection; 4] = [
+    TileDirection::UP,
+    TileDirection::RIGHT,
+    TileDirection::DOWN,
+    TileDirection::LEFT,
+];
+
+pub fn rotate_direction(dir: TileDirection) -> TileDirection {
+    match dir {
+        TileDirection::UP => TileDirection::RIGHT,
+        TileDirection::RIGHT => TileDirection::DOWN,
+        TileDirection::DOWN => TileDirection::LEFT,
+        TileDirection::LEFT => TileDirection::UP,
+        TileDirection::NONE => TileDirection::NONE,
+    }
+}
+
+// Number of quarter turns counterclockwise from the tile as defined in get_tile.
+pub fn direction_to_rotation_steps(dir: TileDirection) -> usize {
+    match dir {
+        TileDirection::UP => 0,
+        TileDirection::RIGHT => 1,
+        TileDirection::DOWN => 2,
+        TileDirection::LEFT => 3,
+        TileDirection::NONE => 0,
+    }
+}
+
+// Absolute sides of a tile on the board. Same order as NEIGHBOR_COORDS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileSide {
+    North,
+    East,
+    South,
+    West,
+}
+
+pub const TILE_SIDES: [TileSide; 4] = [
+    TileSide::North,
+    TileSide::East,
+    TileSide::South,
+    TileSide::West,
+];
+
+// Which way a river bends on a tile, looking downstream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RiverTurn {
+    Left,
+    Right,
+}
+
+// Way a bridge runs across a tile, as seen on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BridgeAxis {
+    NorthSouth,
+    EastWest,
+}
+
+pub const BRIDGE_AXES: [BridgeAxis; 2] = [BridgeAxis::NorthSouth, BridgeAxis::EastWest];
+
+// Middle edges of the two sides the bridge ends on, on the board.
+pub fn bridge_edges(axis: BridgeAxis) -> [EdgeNumber; 2] {
+    match axis {
+        BridgeAxis::NorthSouth => [10, 4],
+        BridgeAxis::EastWest => [1, 7],
+    }
+}
+
+pub fn opposite_side(side: TileSide) -> TileSide {
+    return TILE_SIDES[(side as usize + 2) % 4];
+}
+
+// Edges along a side, clockwise.
+pub fn side_edges(side: TileSide) -> [EdgeNumber; 3] {
+    let first_edge = ((side as usize + 3) % 4) * 3;
+    return [first_edge, first_edge + 1, first_edge + 2];
+}
+
+pub fn edge_side(edge: EdgeNumber) -> TileSide {
+    return TILE_SIDES[(edge / 3 + 1) % 4];
+}
+
+// Where an edge of the tile as defined ends up once the tile is turned to dir.
+pub fn rotate_edge(edge: EdgeNumber, dir: TileDirection) -> EdgeNumber {
+    return (edge + 9 * direction_to_rotation_steps(dir)) % 12;
+}
+
+// Edge of the unturned tile that ends up at the board edge once the tile is turned to dir.
+pub fn unrotate_edge(edge: EdgeNumber, dir: TileDirection) -> EdgeNumber {
+    return (edge + 3 * direction_to_rotation_steps(dir)) % 12;
+}
+
+// The edge of the neighboring tile that touches this edge.
+pub fn opposite_edge(edge: EdgeNumber) -> EdgeNumber {
+    let side = edge / 3;
+    let pos = edge % 3;
+    return ((side + 2) % 4) * 3 + (2 - pos);
+}
+
+// Area sitting on each absolute edge of the tile once it is turned to dir.
+pub fn get_rotated_edge_areas(
+    tile: &Tile,
+    dir: TileDirection,
+    tile_data: &GameTileData,
+) -> [TileAreaIndex; 12] {
+    let mut edge_areas: [TileAreaIndex; 12] = [0; 12];
+    for area_idx in &tile.areas {
+        for edge in &tile_data.all_areas[*area_idx].edges {
+            edge_areas[rotate_edge(*edge, dir)] = *area_idx;
+        }
+    }
+    return edge_areas;
+}
+
+// Areas along each side of the turned tile, indexed by TileSide.
+pub fn get_rotated_side_areas(
+    tile: &Tile,
+    dir: TileDirection,
+    tile_data: &GameTileData,
+) -> [[TileAreaIndex; 3]; 4] {
+    let edge_areas = get_rotated_edge_areas(tile, dir, tile_data);
+    return TILE_SIDES.map(|side| side_edges(side).map(|edge| edge_areas[edge]));
+}
+
+pub fn get_absolute_area_from_relative_area(
+    tile_idx: TileIndex,
+    relative_area_idx: TileAreaIndex,
+    tile_data: &GameTileData,
+) -> TileAreaIndex {
+    return tile_data.all_tiles[tile_idx].areas[relative_area_idx];
+}
+
+// All areas connected across tiles.
+// pub struct MegaArea {
+//     pub connected_areas: Vec<TileAreaIndex>,
+// }
+
+// Tower pieces get stacked on the foundation in the middle of these.
+pub fn tile_type_has_tower_foundation(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::RFRF_02_Tower
+            | TileType::FRRF_12_Tower
+            | TileType::RRRF_Tower
+            | TileType::FFFT_Tower
+            | TileType::TFFT_03_Tower
+            | TileType::TFTF_02_Tower
+    );
+}
+
+// Meeples on a hill win ties for the feature they are in.
+pub fn tile_type_has_hill(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::RFRF_02_Hill
+            | TileType::FRRF_12_Hill
+            | TileType::FFFT_Hill
+            | TileType::TFFT_03_Hill
+    );
+}
+
+// Each one around a completed cloister is worth 3 more points.
+pub fn tile_type_has_vineyard(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::FFFF_Vineyard | TileType::RFRF_02_Vineyard | TileType::FRRF_12_Vineyard
+    );
+}
+
+// Placing one of these opens a bazaar at the end of the turn.
+pub fn tile_type_has_bazaar(tile_type: TileType) -> bool {
+    return matches!(
+        tile_type,
+        TileType::RFRF_02_Bazaar | TileType::FRRF_12_Bazaar | TileType::TFFT_03_Bazaar
+    );
+}
@@ -1,21 +1,52 @@
-use std::f32::consts::PI;
-
 use bevy::{prelude::*, render::mesh::Mesh};
 use bevy_eventlistener::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_mod_raycast::system_param::Raycast;
 use bevy_mod_raycast::system_param::RaycastSettings;
 use bevy_mod_raycast::Ray3d;
-use rand::Rng;
+use rustcassonne_core::game_board::*;
+use rustcassonne_core::players::*;
+use rustcassonne_core::rules::*;
+use rustcassonne_core::scoring::*;
+use rustcassonne_core::tiles::*;
 
-use crate::game_board::*;
-use crate::tiles::*;
+use crate::meeples_render::*;
+use crate::resources::*;
+use crate::settings::*;
 use crate::tiles_render::*;
 
+// Phases of a turn. Each phase only runs the systems for its own action.
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum GamePhase {
+    #[default]
+    DrawTile,
+    PlaceTile,
+    PlaceMeeple,
+    Capture,
+    TendFlock,
+    MoveDragon,
+    Score,
+    BuildCastle,
+    MoveWagon,
+    Bazaar,
+    NextPlayer,
+    GameOver,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct FeatureCompletedEvent {
+    pub feature: Feature,
+}
+
 pub fn handle_tile_drop_event(
     mut drop_event: EventReader<PlaceholderTileDropEvent>,
-    mut gameplay_data: ResMut<GameplayData>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    mut tile_data: ResMut<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
     mut q: Query<(Entity, &mut Transform, &mut TileEntityInfo), Without<MainCamera>>,
+    snap_back_q: Query<&SnapBackPosition>,
+    area_q: Query<(Entity, &AreaEntityInfo)>,
     // camera_q: Query<(&Camera, &OrthographicProjection, &GlobalTransform), With<MainCamera>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -59,26 +90,912 @@ pub fn handle_tile_drop_event(
             return;
         }
 
+        // Every edge has to agree with the tiles already around the placeholder.
+        let Some(&t_coords) = gameplay_data.board_tile_matrix_inverse.get(&t_tile_index) else {
+            println!("target is not on the game board, ignoring");
+            return;
+        };
+        let fits = tile_fits_at(
+            &gameplay_data,
+            &tile_data,
+            dropped_tile_info.tile_idx,
+            dropped_tile_info.dir,
+            t_coords,
+        );
+        // A bridge goes across the tile only if the player asked for one with V.
+        let possible_bridge = get_bridge_for_fit(
+            &gameplay_data,
+            &tile_data,
+            &players,
+            dropped_tile_info.tile_idx,
+            dropped_tile_info.dir,
+            t_coords,
+        );
+        let bridge = possible_bridge.filter(|_| gameplay_data.bridge_requested);
+        if !fits && bridge.is_none() {
+            println!(
+                "tile {:?} does not fit at {:?}, snapping back",
+                dropped_tile_info.tile_idx, t_coords
+            );
+            if possible_bridge.is_some() {
+                println!("press V to lay a bridge that would make it fit");
+            }
+            if let Ok(snap_back) = snap_back_q.get(event.dropped) {
+                dropped_transform.translation.x = snap_back.0.x;
+                dropped_transform.translation.y = snap_back.0.y;
+            }
+            return;
+        }
+
         dropped_transform.translation.x = t_transform.translation.x;
         dropped_transform.translation.y = t_transform.translation.y;
+        let dropped_tile_index = dropped_tile_info.tile_idx;
 
         replace_placeholder_tile_on_board(
             &mut gameplay_data,
-            dropped_tile_info.tile_idx,
+            &tile_data,
+            dropped_tile_index,
             t_tile_index,
             &mut commands,
             &mut meshes,
             &mut materials,
             &mut q,
         );
+        gameplay_data.bridge_requested = false;
+        if let Some(axis) = bridge {
+            let bridge_area = place_bridge(
+                &mut gameplay_data,
+                &mut tile_data,
+                &mut players,
+                dropped_tile_index,
+                axis,
+            );
+            println!("{} builds a bridge", players.current().name);
+            create_bridge(
+                dropped_tile_index,
+                bridge_area,
+                axis,
+                t_transform.translation.truncate(),
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+            );
+        }
+
+        // The tile's areas can take a meeple now that the tile no longer covers them.
+        for (area_entity, area_info) in area_q.iter() {
+            if area_info.tile_idx == dropped_tile_index {
+                commands.entity(area_entity).insert(Pickable::default());
+            }
+        }
+        next_phase.set(GamePhase::PlaceMeeple);
+    }
+}
+
+pub fn handle_meeple_drop_event(
+    mut drop_event: EventReader<MeepleDropEvent>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    area_q: Query<&AreaEntityInfo>,
+    mut commands: Commands,
+) {
+    for event in drop_event.iter() {
+        let Ok((_, mut meeple_transform, &meeple_info)) = meeple_q.get_mut(event.dropped) else {
+            println!("dropped is not a meeple, ignoring");
+            return;
+        };
+        let Ok(area_info) = area_q.get(event.target) else {
+            println!("target is not an area, ignoring");
+            return;
+        };
+
+        if meeple_info.player_idx != players.current_player {
+            println!("not this player's meeple, snapping back");
+            meeple_transform.translation.x = meeple_info.tray_position.x;
+            meeple_transform.translation.y = meeple_info.tray_position.y;
+            return;
+        }
+        if !meeple_area_is_reachable(&gameplay_data, &tile_data, area_info.area_idx) {
+            println!("meeples can only go on the tile just placed, snapping back");
+            meeple_transform.translation.x = meeple_info.tray_position.x;
+            meeple_transform.translation.y = meeple_info.tray_position.y;
+            return;
+        }
+        let placed_meeple = PlacedMeeple {
+            meeple: meeple_info.meeple_idx,
+            player: meeple_info.player_idx,
+        };
+        if !figure_can_be_placed(
+            &gameplay_data,
+            &tile_data,
+            area_info.area_idx,
+            placed_meeple,
+        ) {
+            println!(
+                "area {:?} can't take a meeple, snapping back",
+                area_info.area_idx
+            );
+            meeple_transform.translation.x = meeple_info.tray_position.x;
+            meeple_transform.translation.y = meeple_info.tray_position.y;
+            return;
+        }
+
+        if let Some(position) = event.position {
+            meeple_transform.translation.x = position.x;
+            meeple_transform.translation.y = position.y;
+        }
+        place_meeple(
+            &mut gameplay_data,
+            &mut players,
+            area_info.area_idx,
+            placed_meeple,
+        );
+
+        pin_meeple(event.dropped, &mut commands);
+        println!(
+            "player {:?} placed meeple {:?} on area {:?}",
+            meeple_info.player_idx, meeple_info.meeple_idx, area_info.area_idx
+        );
+        if figure_kind(meeple_info.meeple_idx) == FigureKind::Barn {
+            let returned_meeples = score_barn_placement(
+                &mut gameplay_data,
+                &tile_data,
+                &mut players,
+                area_info.area_idx,
+            );
+            println!("the barn sent home {:?}", returned_meeples);
+            return_meeples_to_trays(&returned_meeples, &mut meeple_q, &mut commands);
+        }
+        if figure_kind(meeple_info.meeple_idx) == FigureKind::Shepherd {
+            let (token, returned_meeples) =
+                grow_flock(&mut gameplay_data, &mut players, area_info.area_idx);
+            println!("the shepherd drew {:?}", token);
+            return_meeples_to_trays(&returned_meeples, &mut meeple_q, &mut commands);
+        }
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+    }
+}
+
+// Placed meeples stay put until their feature is scored.
+fn pin_meeple(meeple: Entity, commands: &mut Commands) {
+    commands.entity(meeple).remove::<On<Pointer<Drag>>>();
+    commands.entity(meeple).remove::<On<Pointer<DragStart>>>();
+    commands.entity(meeple).remove::<On<Pointer<DragEnd>>>();
+}
+
+// Figures can only be put down while placing a meeple or moving a wagon. Dropped any other
+// time, a meeple goes back to its tray.
+pub fn snap_back_dropped_meeples(
+    mut drop_event: EventReader<MeepleDropEvent>,
+    phase: Res<State<GamePhase>>,
+    mut meeple_q: Query<(&mut Transform, &MeepleEntityInfo)>,
+) {
+    for event in drop_event.iter() {
+        if matches!(phase.get(), GamePhase::PlaceMeeple | GamePhase::MoveWagon) {
+            continue;
+        }
+        let Ok((mut meeple_transform, meeple_info)) = meeple_q.get_mut(event.dropped) else {
+            continue;
+        };
+        println!("meeples can't be placed now, snapping back");
+        meeple_transform.translation.x = meeple_info.tray_position.x;
+        meeple_transform.translation.y = meeple_info.tray_position.y;
+    }
+}
+
+// Placing a meeple is optional.
+pub fn skip_meeple_placement(
+    keys: Res<Input<KeyCode>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    players: Res<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    if keys.just_pressed(KeyCode::N) {
+        println!("no meeple placed");
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+    }
+}
+
+// Instead of placing a meeple, the player can take their abbot back with F and score it.
+pub fn recall_abbot_with_key(
+    keys: Res<Input<KeyCode>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::F) {
+        return;
+    }
+    let Some((abbot, points)) = recall_abbot(&mut gameplay_data, &tile_data, &mut players) else {
+        println!("{} has no abbot out", players.current().name);
+        return;
+    };
+    println!(
+        "{} recalls their abbot for {} points",
+        players.current().name,
+        points
+    );
+    return_meeples_to_trays(&[abbot], &mut meeple_q, &mut commands);
+    next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+}
+
+// Once the meeple has been placed or skipped, the player's shepherd tends its flock if the
+// tile extended its field.
+fn phase_after_meeple(
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
+    players: &Players,
+) -> GamePhase {
+    let flock_to_tend = gameplay_data.last_placed_tile.and_then(|tile_idx| {
+        get_flock_to_tend(gameplay_data, tile_data, tile_idx, players.current_player)
+    });
+    if flock_to_tend.is_some() {
+        return GamePhase::TendFlock;
+    }
+    return phase_after_flock(gameplay_data, tile_data);
+}
+
+// A dragon tile sets the dragon off after that.
+fn phase_after_flock(gameplay_data: &mut GameplayData, tile_data: &GameTileData) -> GamePhase {
+    if start_dragon_move(gameplay_data, tile_data) {
+        return GamePhase::MoveDragon;
+    }
+    return GamePhase::Score;
+}
+
+// Instead of a meeple, the fairy can go next to one of the player's meeples.
+pub fn handle_fairy_drop_event(
+    mut drop_event: EventReader<MeepleDropEvent>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    players: Res<PlayersRes>,
+    mut fairy_q: Query<(&mut Transform, &mut FairyEntityInfo)>,
+    area_q: Query<&AreaEntityInfo>,
+) {
+    for event in drop_event.iter() {
+        let Ok((mut fairy_transform, mut fairy_info)) = fairy_q.get_mut(event.dropped) else {
+            continue;
+        };
+        let Ok(area_info) = area_q.get(event.target) else {
+            println!("target is not an area, ignoring");
+            continue;
+        };
+        if !fairy_can_be_placed(&gameplay_data, area_info.area_idx, players.current_player) {
+            println!("the fairy can only go next to your own meeple, snapping back");
+            fairy_transform.translation.x = fairy_info.position.x;
+            fairy_transform.translation.y = fairy_info.position.y;
+            continue;
+        }
+
+        if let Some(position) = event.position {
+            fairy_transform.translation.x = position.x;
+            fairy_transform.translation.y = position.y;
+        }
+        fairy_info.position = fairy_transform.translation.truncate();
+        place_fairy(&mut gameplay_data, area_info.area_idx);
+        println!(
+            "player {:?} moved the fairy to area {:?}",
+            players.current_player, area_info.area_idx
+        );
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+    }
+}
+
+// Clicking a knight in the town the princess joined sends it home, instead of placing a
+// meeple.
+pub fn handle_princess_click(
+    mut click_event: EventReader<Pointer<Click>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    for event in click_event.iter() {
+        let Ok(meeple_idx) = meeple_q
+            .get(event.target)
+            .map(|(_, _, meeple_info)| meeple_info.meeple_idx)
+        else {
+            continue;
+        };
+        let Some(area_idx) = gameplay_data
+            .area_meeples
+            .iter()
+            .find(|(_, m)| m.meeple == meeple_idx)
+            .map(|(a, _)| *a)
+        else {
+            continue;
+        };
+        if !princess_can_remove(&gameplay_data, &tile_data, area_idx) {
+            println!("the princess can't send meeple {:?} home", meeple_idx);
+            continue;
+        }
+        let removed_meeples: Vec<PlacedMeeple> =
+            remove_meeple(&mut gameplay_data, &mut players, area_idx)
+                .into_iter()
+                .collect();
+        println!("the princess sent home {:?}", removed_meeples);
+        return_meeples_to_trays(&removed_meeples, &mut meeple_q, &mut commands);
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+        return;
+    }
+}
+
+// Clicking a tile with a tower foundation stacks a tower piece on it, instead of placing a
+// meeple.
+pub fn handle_tower_click(
+    mut click_event: EventReader<Pointer<Click>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    area_q: Query<&AreaEntityInfo>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for event in click_event.iter() {
+        let Ok(area_info) = area_q.get(event.target) else {
+            continue;
+        };
+        let tile_idx = tile_data.all_areas[area_info.area_idx].tile_idx;
+        let Some(&coords) = gameplay_data.board_tile_matrix_inverse.get(&tile_idx) else {
+            continue;
+        };
+        let Some(height) = build_tower(&mut gameplay_data, &tile_data, &mut players, coords) else {
+            continue;
+        };
+        println!(
+            "{} built the tower at {:?} up to {}",
+            players.current().name,
+            coords,
+            height
+        );
+        create_tower_piece(coords, height, &mut commands, &mut meshes, &mut materials);
+        if get_tower_captures(&gameplay_data, &tile_data, coords).is_empty() {
+            next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+        } else {
+            next_phase.set(GamePhase::Capture);
+        }
+        return;
+    }
+}
+
+// Runs on entering GamePhase::Capture.
+pub fn announce_capture(players: Res<PlayersRes>) {
+    println!(
+        "{} can click a meeple the tower sees to capture it, or N to let them be",
+        players.current().name
+    );
+}
+
+pub fn handle_capture_click(
+    mut click_event: EventReader<Pointer<Click>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    let Some(tower) = gameplay_data.tower_built else {
+        return;
+    };
+    for event in click_event.iter() {
+        let Ok(meeple_idx) = meeple_q
+            .get(event.target)
+            .map(|(_, _, meeple_info)| meeple_info.meeple_idx)
+        else {
+            continue;
+        };
+        let Some(area_idx) = get_tower_captures(&gameplay_data, &tile_data, tower)
+            .into_iter()
+            .find(|a| gameplay_data.area_meeples[a].meeple == meeple_idx)
+        else {
+            println!("the tower can't see meeple {:?}", meeple_idx);
+            continue;
+        };
+        let (captured, released) = capture_meeple(&mut gameplay_data, &mut players, area_idx);
+        let Some(captured) = captured else {
+            continue;
+        };
+        println!(
+            "{} captured {:?}, prisoners swapped back: {:?}",
+            players.current().name,
+            captured,
+            released
+        );
+        if captured.player == players.current_player {
+            return_meeples_to_trays(&[captured], &mut meeple_q, &mut commands);
+        } else {
+            commands.entity(event.target).insert(Visibility::Hidden);
+        }
+        return_meeples_to_trays(&released, &mut meeple_q, &mut commands);
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+        return;
+    }
+}
+
+// Capturing is optional.
+pub fn skip_capture(
+    keys: Res<Input<KeyCode>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    players: Res<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    if keys.just_pressed(KeyCode::N) {
+        println!("nobody captured");
+        next_phase.set(phase_after_meeple(&mut gameplay_data, &tile_data, &players));
+    }
+}
+
+// Before drawing, M buys back one of the current player's captured meeples.
+pub fn pay_ransom_with_key(
+    keys: Res<Input<KeyCode>>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    if keys.just_pressed(KeyCode::M) {
+        let Some(prisoner) = pay_ransom(&mut players) else {
+            println!(
+                "no meeple to buy back, or fewer than {} points to pay with",
+                RANSOM_POINTS
+            );
+            return;
+        };
+        println!(
+            "{} paid {} points to get meeple {:?} back",
+            players.current().name,
+            RANSOM_POINTS,
+            prisoner.meeple
+        );
+        return_meeples_to_trays(&[prisoner], &mut meeple_q, &mut commands);
+    }
+}
+
+// Runs on entering GamePhase::TendFlock.
+pub fn announce_flock(players: Res<PlayersRes>) {
+    println!(
+        "{} can grow their flock with G or drive it home with H",
+        players.current().name
+    );
+}
+
+// G draws another token for the flock, H scores it.
+pub fn tend_flock_with_keys(
+    keys: Res<Input<KeyCode>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    let grow = keys.just_pressed(KeyCode::G);
+    if !grow && !keys.just_pressed(KeyCode::H) {
+        return;
+    }
+    let Some(tile_idx) = gameplay_data.last_placed_tile else {
+        return;
+    };
+    let Some(shepherd_area) =
+        get_flock_to_tend(&gameplay_data, &tile_data, tile_idx, players.current_player)
+    else {
+        return;
+    };
+    let returned_meeples = if grow {
+        let (token, returned_meeples) = grow_flock(&mut gameplay_data, &mut players, shepherd_area);
+        println!("the shepherd drew {:?}", token);
+        returned_meeples
+    } else {
+        let returned_meeples = drive_flock_home(&mut gameplay_data, &mut players, shepherd_area);
+        println!("the flock went home with {:?}", returned_meeples);
+        returned_meeples
+    };
+    return_meeples_to_trays(&returned_meeples, &mut meeple_q, &mut commands);
+    next_phase.set(phase_after_flock(&mut gameplay_data, &tile_data));
+}
+
+// Runs on entering GamePhase::MoveDragon.
+pub fn announce_dragon_move(gameplay_data: Res<GameplayDataRes>, players: Res<PlayersRes>) {
+    let mover = get_dragon_mover(&gameplay_data, &players);
+    println!(
+        "the dragon moves {} times, {} goes first (I/J/K/L)",
+        gameplay_data.dragon_moves_left, players.players[mover].name
+    );
+}
+
+// I, L, K and J step the dragon north, east, south and west.
+pub fn move_dragon_with_keys(
+    keys: Res<Input<KeyCode>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    let side = if keys.just_pressed(KeyCode::I) {
+        TileSide::North
+    } else if keys.just_pressed(KeyCode::L) {
+        TileSide::East
+    } else if keys.just_pressed(KeyCode::K) {
+        TileSide::South
+    } else if keys.just_pressed(KeyCode::J) {
+        TileSide::West
+    } else {
+        return;
+    };
+    let Some(dragon) = gameplay_data.dragon else {
+        return;
+    };
+    let coords = get_neighbor_coords(dragon, side);
+    if !get_dragon_moves(&gameplay_data, &tile_data).contains(&coords) {
+        println!("the dragon can't go to {:?}", coords);
+        return;
+    }
+    let eaten_meeples = move_dragon(&mut gameplay_data, &tile_data, &mut players, coords);
+    println!(
+        "the dragon moved to {:?} and ate {:?}",
+        coords, eaten_meeples
+    );
+    return_meeples_to_trays(&eaten_meeples, &mut meeple_q, &mut commands);
+    if gameplay_data.dragon_moves_left == 0 {
+        next_phase.set(GamePhase::Score);
+    } else {
+        let mover = get_dragon_mover(&gameplay_data, &players);
+        println!("{} moves the dragon next", players.players[mover].name);
+    }
+}
+
+// Runs on entering GamePhase::Score, after the meeple has been placed or skipped.
+pub fn score_placed_tile(
+    settings: Res<GameSettings>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    players: Res<PlayersRes>,
+    mut feature_completed_event: EventWriter<FeatureCompletedEvent>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    if let Some(tile_idx) = gameplay_data.last_placed_tile {
+        // Checked before the completed features give their builders back.
+        let extra_turn_earned =
+            builder_earns_extra_turn(&gameplay_data, &tile_data, tile_idx, players.current_player);
+        gameplay_data.extra_turn_earned = extra_turn_earned;
+        for feature in find_completed_features(&gameplay_data, &tile_data, tile_idx) {
+            println!("completed feature: {:?}", feature);
+            // Small towns wait for the player to decide whether they become castles.
+            if settings
+                .expansions
+                .contains(&Expansion::BridgesCastlesBazaars)
+                && feature_can_become_castle(&gameplay_data, &feature)
+            {
+                gameplay_data.pending_castles.push(feature);
+                continue;
+            }
+            feature_completed_event.send(FeatureCompletedEvent { feature });
+        }
+    }
+    if !gameplay_data.pending_castles.is_empty() {
+        next_phase.set(GamePhase::BuildCastle);
+        return;
+    }
+    next_phase.set(GamePhase::NextPlayer);
+}
+
+// Runs on entering GamePhase::BuildCastle.
+pub fn announce_castle(gameplay_data: Res<GameplayDataRes>, players: Res<PlayersRes>) {
+    println!(
+        "{}: C to turn the completed town on tiles {:?} into a castle, or N to score it",
+        players.current().name,
+        gameplay_data.pending_castles[0].tiles
+    );
+}
+
+// One completed town at a time: C builds a castle from it, N scores it as usual.
+pub fn build_castle_with_keys(
+    keys: Res<Input<KeyCode>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut feature_completed_event: EventWriter<FeatureCompletedEvent>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    let Some(feature) = gameplay_data.pending_castles.first().cloned() else {
+        next_phase.set(GamePhase::NextPlayer);
+        return;
+    };
+    if keys.just_pressed(KeyCode::C) {
+        build_castle(&mut gameplay_data, &feature);
+        println!("{} builds a castle", players.current().name);
+        let current_player = players.current_player;
+        award_goods(&feature, &tile_data, &mut players.players[current_player]);
+    } else if keys.just_pressed(KeyCode::N) {
+        feature_completed_event.send(FeatureCompletedEvent { feature });
+    } else {
+        return;
+    }
+    gameplay_data.pending_castles.remove(0);
+    if gameplay_data.pending_castles.is_empty() {
+        next_phase.set(GamePhase::NextPlayer);
+        return;
+    }
+    println!(
+        "C to turn the completed town on tiles {:?} into a castle, or N to score it",
+        gameplay_data.pending_castles[0].tiles
+    );
+}
+
+// Runs on entering GamePhase::MoveWagon.
+pub fn announce_wagon_move(gameplay_data: Res<GameplayDataRes>, players: Res<PlayersRes>) {
+    let (wagon, _) = gameplay_data.pending_wagons[0];
+    println!(
+        "{} can move their wagon to a road, town or cloister next to where it was, or N to keep it",
+        players.players[wagon.player].name
+    );
+}
+
+pub fn handle_wagon_drop_event(
+    mut drop_event: EventReader<MeepleDropEvent>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(&mut Transform, &MeepleEntityInfo)>,
+    area_q: Query<&AreaEntityInfo>,
+    mut commands: Commands,
+) {
+    for event in drop_event.iter() {
+        let Ok((mut meeple_transform, meeple_info)) = meeple_q.get_mut(event.dropped) else {
+            continue;
+        };
+        let (wagon, from_area) = gameplay_data.pending_wagons[0];
+        let target_area = area_q.get(event.target).map(|a| a.area_idx);
+        if meeple_info.meeple_idx != wagon.meeple
+            || !target_area.is_ok_and(|a| {
+                get_wagon_destinations(&gameplay_data, &tile_data, from_area).contains(&a)
+            })
+        {
+            println!("only the wagon can move, to a spot next to where it was, snapping back");
+            meeple_transform.translation.x = meeple_info.tray_position.x;
+            meeple_transform.translation.y = meeple_info.tray_position.y;
+            continue;
+        }
+
+        if let Some(position) = event.position {
+            meeple_transform.translation.x = position.x;
+            meeple_transform.translation.y = position.y;
+        }
+        let area_idx = target_area.unwrap();
+        place_meeple(&mut gameplay_data, &mut players, area_idx, wagon);
+        pin_meeple(event.dropped, &mut commands);
+        println!("wagon {:?} moved to area {:?}", wagon.meeple, area_idx);
+        gameplay_data.pending_wagons.remove(0);
+        next_phase.set(GamePhase::NextPlayer);
+        return;
+    }
+}
+
+// The wagon can also just stay home.
+pub fn skip_wagon_move(
+    keys: Res<Input<KeyCode>>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    if keys.just_pressed(KeyCode::N) {
+        println!("wagon not moved");
+        gameplay_data.pending_wagons.remove(0);
+        next_phase.set(GamePhase::NextPlayer);
+    }
+}
+
+// Bridges, Castles & Bazaars. Prints what the auction is waiting on.
+fn announce_auction(gameplay_data: &GameplayData, players: &Players) {
+    let Some(auction) = &gameplay_data.auction else {
+        println!("the bazaar is closed");
+        return;
+    };
+    if let Some(&bidder) = auction.bidders.first() {
+        println!(
+            "{} auctions tile {:?}, high bid {} by {}. {}: U/O to change your bid, X to bid",
+            players.players[auction.auctioneer].name,
+            auction.tile,
+            auction.high_bid,
+            players.players[auction.high_bidder].name,
+            players.players[bidder].name
+        );
+    } else {
+        println!(
+            "{}: C to buy tile {:?} from {} for {}, or X to sell it to them",
+            players.players[auction.auctioneer].name,
+            auction.tile,
+            players.players[auction.high_bidder].name,
+            auction.high_bid
+        );
+    }
+}
+
+// Runs on entering GamePhase::Bazaar.
+pub fn announce_bazaar(gameplay_data: Res<GameplayDataRes>, players: Res<PlayersRes>) {
+    println!("the bazaar opens with tiles {:?}", gameplay_data.market);
+    announce_auction(&gameplay_data, &players);
+}
+
+// Each bidder picks a bid with U and O and places it with X. Once everyone has bid, the
+// auctioneer buys the tile with C or sells it with X.
+pub fn auction_with_keys(
+    keys: Res<Input<KeyCode>>,
+    mut offer: Local<i32>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    let Some(auction) = gameplay_data.auction.clone() else {
+        next_phase.set(GamePhase::NextPlayer);
+        return;
+    };
+    if !auction.bidders.is_empty() {
+        if keys.just_pressed(KeyCode::U) {
+            *offer += 1;
+            println!("bid {}", *offer);
+        } else if keys.just_pressed(KeyCode::O) && *offer > 0 {
+            *offer -= 1;
+            println!("bid {}", *offer);
+        } else if keys.just_pressed(KeyCode::X) {
+            bid_in_auction(&mut gameplay_data, &players, *offer);
+            *offer = 0;
+            // Nobody outbid the auctioneer, who takes the tile for free.
+            let unopposed = gameplay_data
+                .auction
+                .as_ref()
+                .is_some_and(|a| a.bidders.is_empty() && a.high_bidder == a.auctioneer);
+            if unopposed {
+                close_auction(&mut gameplay_data, &mut players, true);
+            }
+            announce_auction(&gameplay_data, &players);
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::C) {
+        if !close_auction(&mut gameplay_data, &mut players, true) {
+            println!(
+                "{} can't pay {}, X to sell the tile instead",
+                players.players[auction.auctioneer].name, auction.high_bid
+            );
+            return;
+        }
+        announce_auction(&gameplay_data, &players);
+    } else if keys.just_pressed(KeyCode::X) {
+        close_auction(&mut gameplay_data, &mut players, false);
+        announce_auction(&gameplay_data, &players);
+    }
+}
+
+// Runs on entering GamePhase::NextPlayer. The game ends once the last tile has been played.
+pub fn next_player(
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    gameplay_data.last_placed_tile = None;
+    gameplay_data.tower_built = None;
+    gameplay_data.bridge_requested = false;
+    // Wagons from features completed this turn move on first, one at a time.
+    keep_movable_wagons(&mut gameplay_data, &tile_data);
+    if !gameplay_data.pending_wagons.is_empty() {
+        next_phase.set(GamePhase::MoveWagon);
+        return;
+    }
+    // A bazaar placed this turn opens its market before anyone plays on.
+    if gameplay_data.bazaar_pending && open_bazaar(&mut gameplay_data, &mut players) {
+        next_phase.set(GamePhase::Bazaar);
+        return;
+    }
+    if gameplay_data.all_tiles_drawn() && players.players.iter().all(|p| p.bought_tiles.is_empty())
+    {
+        next_phase.set(GamePhase::GameOver);
+        return;
+    }
+    if gameplay_data.extra_turn_earned {
+        gameplay_data.extra_turn_earned = false;
+        gameplay_data.in_extra_turn = true;
+        println!("{}'s builder earned another turn", players.current().name);
+        next_phase.set(GamePhase::DrawTile);
+        return;
+    }
+    gameplay_data.in_extra_turn = false;
+    players.advance_turn();
+    score_fairy_turn_start(&gameplay_data, &mut players);
+    println!("{}'s turn", players.current().name);
+    next_phase.set(GamePhase::DrawTile);
+}
+
+// Runs on entering GamePhase::GameOver.
+pub fn score_game_over(
+    tile_data: Res<TileDataRes>,
+    gameplay_data: Res<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+) {
+    score_incomplete_features(&gameplay_data, &tile_data, &mut players.players);
+    score_farms(&gameplay_data, &tile_data, &mut players.players);
+    score_goods(&mut players.players);
+    println!("game over");
+    for player in &players.players {
+        println!(
+            "{} ({:?}): {:?} points",
+            player.name, player.team, player.points
+        );
+    }
+}
+
+pub fn handle_feature_completed_event(
+    mut feature_completed_event: EventReader<FeatureCompletedEvent>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut meeple_q: Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    mut commands: Commands,
+) {
+    let features: Vec<Feature> = feature_completed_event
+        .iter()
+        .map(|event| event.feature.clone())
+        .collect();
+    // Castles built on earlier turns score before any new ones go up.
+    let castle_meeples = score_castles(&features, &mut gameplay_data, &tile_data, &mut players);
+    return_meeples_to_trays(&castle_meeples, &mut meeple_q, &mut commands);
+
+    for feature in &features {
+        let current_player = players.current_player;
+        let owners =
+            score_completed_feature(feature, &gameplay_data, &tile_data, &mut players.players);
+        println!(
+            "{:?} scored {:?} points for players {:?}",
+            feature.feature_type,
+            score_feature(feature, &tile_data, true),
+            owners
+        );
+        award_goods(feature, &tile_data, &mut players.players[current_player]);
+
+        let returned_meeples =
+            return_meeples_from_feature(feature, &mut gameplay_data, &mut players);
+        return_meeples_to_trays(&returned_meeples, &mut meeple_q, &mut commands);
+    }
+}
+
+fn return_meeples_to_trays(
+    returned_meeples: &[PlacedMeeple],
+    meeple_q: &mut Query<(Entity, &mut Transform, &MeepleEntityInfo)>,
+    commands: &mut Commands,
+) {
+    for (meeple, mut meeple_transform, meeple_info) in meeple_q.iter_mut() {
+        if returned_meeples
+            .iter()
+            .any(|m| m.meeple == meeple_info.meeple_idx)
+        {
+            return_meeple_to_tray(meeple, &mut meeple_transform, meeple_info, commands);
+        }
     }
 }
 
 pub fn spawn_tile(
     keys: Res<Input<KeyCode>>,
     window: Query<&Window>,
-    tile_data: ResMut<GameTileData>,
-    mut gameplay_data: ResMut<GameplayData>,
+    tile_data: ResMut<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
@@ -86,14 +1003,30 @@ pub fn spawn_tile(
     camera_q: Query<(Entity, &mut Camera, &mut Transform, &GlobalTransform), With<MainCamera>>,
 ) {
     if keys.just_pressed(KeyCode::T) {
-        if gameplay_data.unspawned_tiles.len() == 0 {
+        let num_discarded = gameplay_data.discarded_tiles.len();
+        let num_set_aside = gameplay_data.set_aside_tiles.len();
+        // Tiles bought at a bazaar are played before drawing new ones.
+        let next_tile = take_bought_tile(&mut gameplay_data, &tile_data, &mut players)
+            .or_else(|| draw_tile(&mut gameplay_data, &tile_data, &players));
+        for tile_idx in &gameplay_data.set_aside_tiles[num_set_aside..] {
+            println!("tile {:?} needs the dragon out, setting it aside", tile_idx);
+        }
+        for tile_idx in &gameplay_data.discarded_tiles[num_discarded..] {
+            println!(
+                "tile {:?} ({:?}) can't be placed anywhere, discarding it",
+                tile_idx, tile_data.all_tiles[*tile_idx].tile_type
+            );
+        }
+        let Some(next_tile) = next_tile else {
+            if players.players.iter().any(|p| !p.bought_tiles.is_empty()) {
+                println!("No more tiles for {}", players.current().name);
+                next_phase.set(GamePhase::NextPlayer);
+                return;
+            }
             println!("No more tiles");
+            next_phase.set(GamePhase::GameOver);
             return;
-        }
-
-        let next_tile_index: usize =
-            rand::thread_rng().gen_range(0..gameplay_data.unspawned_tiles.len());
-        let next_tile: TileIndex = gameplay_data.unspawned_tiles[next_tile_index];
+        };
 
         println!("spawn tile {:?}", next_tile);
         create_tile(
@@ -107,44 +1040,65 @@ pub fn spawn_tile(
             camera_q.single().3,
         );
 
-        gameplay_data.unspawned_tiles.remove(next_tile_index);
         gameplay_data.spawned_tiles.push(next_tile);
-
-        // First tile needs a placeholder tile to start the graph with.
-        if gameplay_data.next_placeholder_index == PLACEHOLDER_TILE_OFFSET {
-            add_placeholder_tile_and_maybe_init_board(
-                &mut gameplay_data,
-                window,
-                &mut commands,
-                &mut meshes,
-                &mut materials,
-                camera_q,
-            );
-        }
+        next_phase.set(GamePhase::PlaceTile);
     }
 }
 
-pub fn spawn_placeholder_tile(
+// Instead of drawing, a player can play their abbey into a hole on the board.
+pub fn play_abbey(
     keys: Res<Input<KeyCode>>,
     window: Query<&Window>,
-    _tile_data: ResMut<GameTileData>,
-    mut gameplay_data: ResMut<GameplayData>,
+    tile_data: Res<TileDataRes>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    mut players: ResMut<PlayersRes>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    _q: Query<(Entity, &mut Transform, &mut TileEntityInfo), Without<MainCamera>>,
-    camera_q: Query<(Entity, &mut Camera, &mut Transform, &GlobalTransform), With<MainCamera>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
 ) {
-    if keys.just_pressed(KeyCode::Y) {
-        println!("spawn placeholder tile");
-        add_placeholder_tile_and_maybe_init_board(
-            &mut gameplay_data,
-            window,
+    if keys.just_pressed(KeyCode::B) {
+        let Some(abbey) = take_abbey(&mut gameplay_data, &tile_data, &mut players) else {
+            println!("no abbey to play or nowhere to put it");
+            return;
+        };
+
+        println!("{} plays their abbey {:?}", players.current().name, abbey);
+        create_tile(
+            abbey,
+            window.single(),
+            &tile_data,
             &mut commands,
             &mut meshes,
             &mut materials,
-            camera_q,
+            camera_q.single().0,
+            camera_q.single().1,
         );
+
+        gameplay_data.spawned_tiles.push(abbey);
+        next_phase.set(GamePhase::PlaceTile);
+    }
+}
+
+// V asks for a bridge across the tile being placed, or takes the request back.
+pub fn request_bridge(
+    keys: Res<Input<KeyCode>>,
+    mut gameplay_data: ResMut<GameplayDataRes>,
+    players: Res<PlayersRes>,
+) {
+    if !keys.just_pressed(KeyCode::V) {
+        return;
+    }
+    if players.current().bridges == 0 {
+        println!("{} has no bridges left", players.current().name);
+        return;
+    }
+    gameplay_data.bridge_requested = !gameplay_data.bridge_requested;
+    if gameplay_data.bridge_requested {
+        println!("the tile will get a bridge if one fits");
+    } else {
+        println!("no bridge on this tile");
     }
 }
 
@@ -152,7 +1106,8 @@ pub fn rotate_tile(
     keys: Res<Input<KeyCode>>,
     window: Query<&Window>,
     mut raycast: Raycast,
-    _tile_data: ResMut<GameTileData>,
+    _tile_data: ResMut<TileDataRes>,
+    gameplay_data: Res<GameplayDataRes>,
     mut q: Query<(Entity, &mut Transform, &mut TileEntityInfo), Without<MainCamera>>,
     camera_q: Query<(Entity, &mut Camera, &mut Transform, &GlobalTransform), With<MainCamera>>,
 ) {
@@ -175,11 +1130,20 @@ pub fn rotate_tile(
         let hits = raycast.cast_ray(ray, &RaycastSettings::default());
         for hit in hits {
             if let Ok((_e, mut _e_transform, mut tile_info)) = q.get_mut(hit.0) {
+                // Tiles on the board are locked in.
+                if gameplay_data
+                    .board_tile_matrix_inverse
+                    .contains_key(&tile_info.tile_idx)
+                {
+                    println!("tile is already in game board, not rotating");
+                    return;
+                }
                 tile_info.dir = rotate_direction(tile_info.dir.clone());
                 let tile_idx = tile_info.tile_idx;
+                let dir = tile_info.dir;
                 for mut tile in q.iter_mut() {
                     if tile.2.tile_idx == tile_idx {
-                        tile.1.rotate_z(PI / 2.0);
+                        tile.1.rotation = Quat::from_rotation_z(direction_to_angle(dir));
                     }
                 }
                 return;
@@ -202,45 +1166,9 @@ fn get_data_of_tile(
     return None;
 }
 
-// Nonexistant adjacent_tile results in placing at mouse position
-fn add_placeholder_tile_and_maybe_init_board(
-    gameplay_data: &mut ResMut<GameplayData>,
-    window: Query<&Window>,
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
-    camera_q: Query<(Entity, &mut Camera, &mut Transform, &GlobalTransform), With<MainCamera>>,
-) {
-    let next_placeholder_index = gameplay_data.next_placeholder_index;
-    create_placeholder_tile(
-        next_placeholder_index,
-        commands,
-        meshes,
-        materials,
-        mouse_to_world_position(window.single(), camera_q.single().1, camera_q.single().3),
-        true
-    );
-    gameplay_data.next_placeholder_index += 1;
-
-    // If origin tile exists already, don't add it to the matrix.
-    if gameplay_data
-        .board_tile_matrix
-        .contains_key(&TileMatrixCoords { x: 0, y: 0 })
-    {
-        println!("adding additional placeholder tile, it will be free floating");
-        return;
-    }
-
-    gameplay_data
-        .board_tile_matrix
-        .insert(TileMatrixCoords { x: 0, y: 0 }, next_placeholder_index);
-    gameplay_data
-        .board_tile_matrix_inverse
-        .insert(next_placeholder_index, TileMatrixCoords { x: 0, y: 0 });
-}
-
 fn replace_placeholder_tile_on_board(
-    gameplay_data: &mut ResMut<GameplayData>,
+    gameplay_data: &mut GameplayData,
+    tile_data: &GameTileData,
     replacement_tile_index: TileIndex,
     origin_tile_index: TileIndex,
     commands: &mut Commands,
@@ -295,55 +1223,27 @@ fn replace_placeholder_tile_on_board(
         .get(&origin_tile_data.2.tile_idx)
         .unwrap()
         .clone();
-    gameplay_data
-        .board_tile_matrix_inverse
-        .remove(&origin_tile_data.2.tile_idx);
-    gameplay_data
-        .board_tile_matrix_inverse
-        .insert(target_tile_data.2.tile_idx, coords);
-    *gameplay_data.board_tile_matrix.get_mut(&coords).unwrap() = target_tile_data.2.tile_idx;
+    let new_placeholders = place_tile(
+        gameplay_data,
+        tile_data,
+        target_tile_data.2.tile_idx,
+        target_tile_data.2.dir,
+        coords,
+    );
 
     // Copy it out before we destroy the placeholder tile.
     let origin_tile_translation = origin_tile_data.1.translation;
     println!("origin_tile_translation: {origin_tile_translation}");
     commands.entity(origin_tile_data.0).despawn();
 
-    // Remove drag function for the new tile.
-    // TODO: still crashes when the tile is dragged over placeholder
-    commands
-        .entity(target_tile_data.0)
-        .remove::<On<Pointer<Drag>>>();
-    commands
-        .entity(target_tile_data.0)
-        .remove::<On<Pointer<Drop>>>();
-    commands
-        .entity(target_tile_data.0)
-        .remove::<On<Pointer<DragStart>>>();
-    commands
-        .entity(target_tile_data.0)
-        .remove::<On<Pointer<DragEnd>>>();
-
-    // insert placeholder tiles around new tile
-    for dir in NEIGHBOR_COORDS {
-        let new_coords = TileMatrixCoords {
-            x: coords.x + dir.x,
-            y: coords.y + dir.y,
-        };
-        if !gameplay_data.board_tile_matrix.contains_key(&new_coords) {
-            let new_pos = Vec2 {
-                x: origin_tile_translation.x + (dir.x as f32 * 180.0),
-                y: origin_tile_translation.y + (dir.y as f32 * 180.0),
-            };
+    lock_tile_in_place(target_tile_data.0, commands);
 
-            let next_placeholder_index = gameplay_data.next_placeholder_index;
-            create_placeholder_tile(next_placeholder_index, commands, meshes, materials, new_pos, false);
-            gameplay_data.next_placeholder_index += 1;
-            gameplay_data
-                .board_tile_matrix
-                .insert(new_coords, next_placeholder_index);
-            gameplay_data
-                .board_tile_matrix_inverse
-                .insert(next_placeholder_index, new_coords);
-        }
+    // Show the placeholders the board put around the new tile.
+    for (new_coords, placeholder_idx) in new_placeholders {
+        let new_pos = Vec2 {
+            x: origin_tile_translation.x + ((new_coords.x - coords.x) as f32 * 180.0),
+            y: origin_tile_translation.y + ((new_coords.y - coords.y) as f32 * 180.0),
+        };
+        create_placeholder_tile(placeholder_idx, commands, meshes, materials, new_pos, false);
     }
 }
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use rustcassonne_core::players::*;
+use rustcassonne_core::tiles::*;
+
+use crate::meeples_render::*;
+use crate::resources::*;
+
+#[derive(Component)]
+pub struct PlayersText;
+
+// The seed sits in the bottom corner so a game can be reported and replayed.
+pub fn spawn_hud(commands: &mut Commands, seed: u64) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        PlayersText,
+    ));
+    commands.spawn(
+        TextBundle::from_section(
+            format!("Seed: {}", seed),
+            TextStyle {
+                font_size: 16.0,
+                color: Color::GRAY,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+    );
+}
+
+// Only shown once the player has some.
+fn format_goods(player: &Player) -> String {
+    if player.goods.iter().all(|g| *g == 0) {
+        return String::new();
+    }
+    return format!(
+        ", wine {} grain {} cloth {}",
+        player.goods[Goods::Wine as usize],
+        player.goods[Goods::Grain as usize],
+        player.goods[Goods::Cloth as usize]
+    );
+}
+
+// Only shown with the Tower expansion.
+fn format_tower(player: &Player) -> String {
+    if player.tower_pieces == 0 && player.prisoners.is_empty() {
+        return String::new();
+    }
+    return format!(
+        ", {} tower pieces, {} prisoners",
+        player.tower_pieces,
+        player.prisoners.len()
+    );
+}
+
+fn format_bazaar(player: &Player) -> String {
+    let mut text = String::new();
+    if player.bridges > 0 {
+        text += &format!(", {} bridges", player.bridges);
+    }
+    if !player.bought_tiles.is_empty() {
+        text += &format!(", {} bought tiles", player.bought_tiles.len());
+    }
+    return text;
+}
+
+// Whose turn it is, plus everyone's score and meeples left.
+pub fn update_players_text(players: Res<PlayersRes>, mut q: Query<&mut Text, With<PlayersText>>) {
+    let mut sections: Vec<TextSection> = vec![];
+    for (player_idx, player) in players.players.iter().enumerate() {
+        let marker = if player_idx == players.current_player {
+            "> "
+        } else {
+            "  "
+        };
+        sections.push(TextSection::new(
+            format!(
+                "{}{}: {} points, {} meeples{}{}{}{}\n",
+                marker,
+                player.name,
+                player.points,
+                player.meeples.len(),
+                format_goods(player),
+                format_tower(player),
+                format_bazaar(player),
+                if player.has_abbey { ", abbey" } else { "" }
+            ),
+            TextStyle {
+                font_size: 20.0,
+                color: team_color_to_color(player.team),
+                ..default()
+            },
+        ));
+    }
+    for mut text in q.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
@@ -1,27 +1,35 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
-mod game_board;
 mod game_logic;
+mod hud;
+mod meeples_render;
 mod myshapes;
-mod players;
-mod tiles;
+mod resources;
+mod settings;
 mod tiles_render;
 mod unused;
 
-use std::f32::consts::PI;
-
-use bevy::{input::mouse::MouseButtonInput, prelude::*, sprite::MaterialMesh2dBundle};
-use bevy_eventlistener::prelude::*;
+use bevy::{input::mouse::MouseButtonInput, prelude::*};
 use bevy_mod_picking::{debug::DebugPickingMode, prelude::*};
+use rustcassonne_core::game_board::*;
+use rustcassonne_core::players::*;
+use rustcassonne_core::rules::*;
+use rustcassonne_core::tiles::*;
 
-use players::NUM_MEEPLES;
-use game_board::*;
 use game_logic::*;
-use tiles::*;
+use hud::*;
+use meeples_render::*;
+use resources::*;
+use settings::*;
 use tiles_render::*;
 
 fn main() {
+    let settings = GameSettings::from_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        std::process::exit(1);
+    });
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -31,26 +39,96 @@ fn main() {
             ..default()
         }))
         .add_plugins(DefaultPickingPlugins)
+        .add_state::<GamePhase>()
         .add_event::<MouseButtonInput>()
         .add_event::<ScaledDragEvent>()
         .add_event::<PlaceholderTileDropEvent>()
+        .add_event::<FeatureCompletedEvent>()
+        .add_event::<MeepleDropEvent>()
         // Disable bevy_mod_picking logging.
         .insert_resource(State::new(DebugPickingMode::Disabled))
+        .insert_resource(settings)
         .add_systems(Startup, setup)
         .add_systems(
             Update,
             (
                 move_camera,
                 zoom_camera,
-                spawn_tile,
                 handle_scaled_drag_event.run_if(on_event::<ScaledDragEvent>()),
-                handle_tile_drop_event.run_if(on_event::<PlaceholderTileDropEvent>()),
-                rotate_tile,
+                handle_feature_completed_event.run_if(on_event::<FeatureCompletedEvent>()),
+                snap_back_dropped_meeples.run_if(on_event::<MeepleDropEvent>()),
                 print_game_data,
                 print_tile_data,
-                spawn_placeholder_tile,
+                update_players_text.run_if(resource_changed::<PlayersRes>()),
+                update_dragon.run_if(resource_changed::<GameplayDataRes>()),
             ),
         )
+        .add_systems(
+            Update,
+            (spawn_tile, play_abbey, pay_ransom_with_key).run_if(in_state(GamePhase::DrawTile)),
+        )
+        .add_systems(
+            Update,
+            (
+                rotate_tile,
+                request_bridge,
+                handle_tile_drop_event.run_if(on_event::<PlaceholderTileDropEvent>()),
+            )
+                .run_if(in_state(GamePhase::PlaceTile)),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_meeple_drop_event.run_if(on_event::<MeepleDropEvent>()),
+                handle_fairy_drop_event.run_if(on_event::<MeepleDropEvent>()),
+                handle_princess_click.run_if(on_event::<Pointer<Click>>()),
+                handle_tower_click.run_if(on_event::<Pointer<Click>>()),
+                skip_meeple_placement,
+                recall_abbot_with_key,
+            )
+                .run_if(in_state(GamePhase::PlaceMeeple)),
+        )
+        .add_systems(OnEnter(GamePhase::Capture), announce_capture)
+        .add_systems(
+            Update,
+            (
+                handle_capture_click.run_if(on_event::<Pointer<Click>>()),
+                skip_capture,
+            )
+                .run_if(in_state(GamePhase::Capture)),
+        )
+        .add_systems(OnEnter(GamePhase::TendFlock), announce_flock)
+        .add_systems(
+            Update,
+            tend_flock_with_keys.run_if(in_state(GamePhase::TendFlock)),
+        )
+        .add_systems(OnEnter(GamePhase::MoveDragon), announce_dragon_move)
+        .add_systems(
+            Update,
+            move_dragon_with_keys.run_if(in_state(GamePhase::MoveDragon)),
+        )
+        .add_systems(OnEnter(GamePhase::Score), score_placed_tile)
+        .add_systems(OnEnter(GamePhase::BuildCastle), announce_castle)
+        .add_systems(
+            Update,
+            build_castle_with_keys.run_if(in_state(GamePhase::BuildCastle)),
+        )
+        .add_systems(OnEnter(GamePhase::MoveWagon), announce_wagon_move)
+        .add_systems(
+            Update,
+            (
+                handle_wagon_drop_event.run_if(on_event::<MeepleDropEvent>()),
+                skip_wagon_move,
+            )
+                .run_if(in_state(GamePhase::MoveWagon)),
+        )
+        .add_systems(OnEnter(GamePhase::Bazaar), announce_bazaar)
+        .add_systems(
+            Update,
+            auction_with_keys.run_if(in_state(GamePhase::Bazaar)),
+        )
+        .add_systems(OnEnter(GamePhase::NextPlayer), next_player)
+        .add_systems(OnEnter(GamePhase::GameOver), score_game_over)
         .run();
 }
 
@@ -94,80 +172,76 @@ fn zoom_camera(
 }
 
 fn setup(
+    settings: Res<GameSettings>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let game_tile_data: GameTileData = Default::default();
-    commands.insert_resource::<GameTileData>(game_tile_data.clone());
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    println!("seed: {}", seed);
+    let game_tile_data: GameTileData = create_tiles(&settings.expansions);
+    let mut gameplay_data = GameplayData::new(&game_tile_data, seed);
 
-    let mut initial_unspawned_tiles: Vec<TileIndex> = vec![];
-    for (i, _tile) in game_tile_data.all_tiles.into_iter().enumerate() {
-        initial_unspawned_tiles.push(i);
+    // The start tile sits at the origin, with room to play on every side.
+    let (start_tile, placeholders) = place_start_tile(&mut gameplay_data, &game_tile_data);
+    let start_tile_entity = create_tile_at(
+        start_tile,
+        Vec2::ZERO,
+        &game_tile_data,
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+    );
+    lock_tile_in_place(start_tile_entity, &mut commands);
+    for (coords, placeholder_idx) in placeholders {
+        create_placeholder_tile(
+            placeholder_idx,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Vec2::new(coords.x as f32 * 180.0, coords.y as f32 * 180.0),
+            false,
+        );
     }
+    commands.insert_resource(GameplayDataRes(gameplay_data));
+    commands.insert_resource(TileDataRes(game_tile_data));
 
-    commands.insert_resource::<GameplayData>(GameplayData {
-        spawned_tiles: vec![],
-        unspawned_tiles: initial_unspawned_tiles,
-        board_tile_matrix: Default::default(),
-        board_tile_matrix_inverse: Default::default(),
-        next_placeholder_index: PLACEHOLDER_TILE_OFFSET,
-        board_area_graph: Default::default(),
-        area_index_to_area_graph_index: Default::default(),
-    });
-
+    // Centered on the start tile.
     commands.spawn((
         Camera2dBundle::default(),
         RaycastPickCamera::default(),
         MainCamera,
     ));
 
-    // TODO: add with proper meeple data
-    let mut y_offset: f32 = 150.0;
-    for color in [
-        Color::DARK_GRAY,
-        Color::CYAN,
-        Color::FUCHSIA,
-        Color::MAROON,
-        Color::INDIGO,
-    ] {
-        let mut x_offset: f32 = 300.0;
-        for _i in 0..NUM_MEEPLES {
-            let mut transform = Transform::from_translation(Vec3::new(x_offset, y_offset, 5.0));
-            transform.rotate_z(PI / 4.0);
-            commands.spawn((
-                MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(shape::Quad::new(Vec2::new(18., 18.)).into())
-                        .into(),
-                    material: materials.add(ColorMaterial::from(color)),
-                    transform: transform,
-                    ..default()
-                },
-                // MeepleIndex,
-                PickableBundle::default(),    // Makes the entity pickable
-                RaycastPickTarget::default(), // Marker for the `bevy_picking_raycast` backend
-                On::<Pointer<DragStart>>::target_insert(Pickable::IGNORE), // Disable picking
-                On::<Pointer<DragEnd>>::target_insert(Pickable {
-                    should_block_lower: true,
-                    should_emit_events: true,
-                }), // Re-enable picking
-                On::<Pointer<Drag>>::send_event::<ScaledDragEvent>(),
-            ));
-
-            x_offset += 40.0;
-        }
-        y_offset -= 50.0
+    let players = Players::new(
+        &settings.player_names,
+        &settings.player_colors,
+        &settings.expansions,
+    );
+    for player in &players.players {
+        println!("{} plays {:?}", player.name, player.team);
+    }
+    create_meeple_trays(&players, &mut commands, &mut meshes, &mut materials);
+    if settings.expansions.contains(&Expansion::PrincessAndDragon) {
+        create_fairy(
+            Vec2::new(260., 150.),
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
+        create_dragon(&mut commands, &mut meshes, &mut materials);
     }
+    spawn_hud(&mut commands, seed);
+    commands.insert_resource(PlayersRes(players));
 }
 
-fn print_game_data(keys: Res<Input<KeyCode>>, game_data: Res<GameplayData>) {
+fn print_game_data(keys: Res<Input<KeyCode>>, game_data: Res<GameplayDataRes>) {
     if keys.just_pressed(KeyCode::P) {
-        game_data.print();
+        print!("{}", game_data.0);
     }
 }
 
-fn print_tile_data(keys: Res<Input<KeyCode>>, tile_data: Res<GameTileData>) {
+fn print_tile_data(keys: Res<Input<KeyCode>>, tile_data: Res<TileDataRes>) {
     if keys.just_pressed(KeyCode::BracketLeft) {
         println!("all_tiles: {:?}", tile_data.all_tiles);
         println!("all_areas: {:?}", tile_data.all_areas);
@@ -0,0 +1,234 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_eventlistener::{callbacks::ListenerInput, prelude::*};
+use bevy_mod_picking::prelude::*;
+
+use rustcassonne_core::game_board::*;
+use rustcassonne_core::players::*;
+
+use crate::resources::*;
+
+use crate::tiles_render::*;
+
+#[derive(Component, Copy, Clone)]
+pub struct MeepleEntityInfo {
+    pub meeple_idx: MeepleIndex,
+    pub player_idx: PlayerIndex,
+    // Spot in the player's tray the meeple returns to.
+    pub tray_position: Vec2,
+}
+
+#[derive(Event)]
+pub struct MeepleDropEvent {
+    pub target: Entity,
+    pub dropped: Entity,
+    pub position: Option<Vec3>,
+}
+
+impl From<ListenerInput<Pointer<Drop>>> for MeepleDropEvent {
+    fn from(event: ListenerInput<Pointer<Drop>>) -> Self {
+        MeepleDropEvent {
+            target: event.target,
+            dropped: event.dropped,
+            position: event.hit.position,
+        }
+    }
+}
+
+pub fn team_color_to_color(team: TeamColor) -> Color {
+    match team {
+        TeamColor::Blue => Color::BLUE,
+        TeamColor::Red => Color::RED,
+        TeamColor::Green => Color::GREEN,
+        TeamColor::Yellow => Color::YELLOW,
+        TeamColor::Black => Color::BLACK,
+        TeamColor::Gray => Color::GRAY,
+        TeamColor::Pink => Color::PINK,
+    }
+}
+
+// One row of meeples per player, in seating order.
+pub fn create_meeple_trays(
+    players: &Players,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    let mut y_offset: f32 = 150.0;
+    for (player_idx, player) in players.players.iter().enumerate() {
+        let mut x_offset: f32 = 300.0;
+        for meeple_idx in &player.meeples {
+            create_meeple(
+                *meeple_idx,
+                player_idx,
+                player.team,
+                Vec2::new(x_offset, y_offset),
+                commands,
+                meshes,
+                materials,
+            );
+            x_offset += 40.0;
+        }
+        y_offset -= 50.0
+    }
+}
+
+pub fn create_meeple(
+    meeple_idx: MeepleIndex,
+    player_idx: PlayerIndex,
+    team: TeamColor,
+    tray_position: Vec2,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    let mut transform = Transform::from_translation(tray_position.extend(5.0));
+    transform.rotate_z(PI / 4.0);
+    let mesh: Mesh = match figure_kind(meeple_idx) {
+        FigureKind::Meeple => shape::Quad::new(Vec2::new(18., 18.)).into(),
+        FigureKind::LargeMeeple => shape::Quad::new(Vec2::new(26., 26.)).into(),
+        FigureKind::Builder => shape::Circle::new(10.).into(),
+        FigureKind::Pig => shape::RegularPolygon::new(12., 3).into(),
+        FigureKind::Mayor => shape::RegularPolygon::new(12., 5).into(),
+        FigureKind::Wagon => shape::Quad::new(Vec2::new(24., 14.)).into(),
+        FigureKind::Barn => shape::RegularPolygon::new(13., 4).into(),
+        FigureKind::Shepherd => shape::RegularPolygon::new(11., 6).into(),
+        FigureKind::Abbot => shape::RegularPolygon::new(11., 8).into(),
+    };
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(mesh).into(),
+                material: materials.add(ColorMaterial::from(team_color_to_color(team))),
+                transform: transform,
+                ..default()
+            },
+            MeepleEntityInfo {
+                meeple_idx,
+                player_idx,
+                tray_position,
+            },
+            PickableBundle::default(),    // Makes the entity pickable
+            RaycastPickTarget::default(), // Marker for the `bevy_picking_raycast` backend
+        ))
+        .insert(meeple_drag_handlers());
+}
+
+pub fn meeple_drag_handlers() -> (
+    On<Pointer<DragStart>>,
+    On<Pointer<DragEnd>>,
+    On<Pointer<Drag>>,
+) {
+    return (
+        On::<Pointer<DragStart>>::target_insert(Pickable::IGNORE), // Disable picking
+        On::<Pointer<DragEnd>>::target_insert(Pickable {
+            should_block_lower: true,
+            should_emit_events: true,
+        }), // Re-enable picking
+        On::<Pointer<Drag>>::send_event::<ScaledDragEvent>(),
+    );
+}
+
+// Puts a meeple back in its tray, draggable again.
+pub fn return_meeple_to_tray(
+    meeple: Entity,
+    meeple_transform: &mut Transform,
+    meeple_info: &MeepleEntityInfo,
+    commands: &mut Commands,
+) {
+    meeple_transform.translation.x = meeple_info.tray_position.x;
+    meeple_transform.translation.y = meeple_info.tray_position.y;
+    commands
+        .entity(meeple)
+        .insert((meeple_drag_handlers(), Visibility::Inherited));
+}
+
+// Tower. Each piece sits a little higher up the tile than the one below it.
+pub fn create_tower_piece(
+    coords: TileMatrixCoords,
+    height: usize,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    let position = Vec3::new(
+        coords.x as f32 * 180.,
+        coords.y as f32 * 180. + height as f32 * 8.,
+        4.0 + height as f32 * 0.01,
+    );
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::Quad::new(Vec2::new(30., 8.)).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(Color::DARK_GRAY)),
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+}
+
+// Princess & Dragon. The fairy belongs to nobody and stays where it was last put.
+#[derive(Component, Copy, Clone)]
+pub struct FairyEntityInfo {
+    pub position: Vec2,
+}
+
+#[derive(Component)]
+pub struct DragonEntity;
+
+pub fn create_fairy(
+    position: Vec2,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(8.).into()).into(),
+                material: materials.add(ColorMaterial::from(Color::WHITE)),
+                transform: Transform::from_translation(position.extend(6.0)),
+                ..default()
+            },
+            FairyEntityInfo { position },
+            PickableBundle::default(),
+            RaycastPickTarget::default(),
+        ))
+        .insert(meeple_drag_handlers());
+}
+
+// Hidden until the first volcano is placed.
+pub fn create_dragon(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(shape::RegularPolygon::new(40., 6).into()).into(),
+            material: materials.add(ColorMaterial::from(Color::ORANGE_RED)),
+            transform: Transform::from_translation(Vec3::new(0., 0., 4.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DragonEntity,
+        Pickable::IGNORE,
+    ));
+}
+
+pub fn update_dragon(
+    gameplay_data: Res<GameplayDataRes>,
+    mut dragon_q: Query<(&mut Transform, &mut Visibility), With<DragonEntity>>,
+) {
+    let Some(dragon) = gameplay_data.dragon else {
+        return;
+    };
+    for (mut transform, mut visibility) in dragon_q.iter_mut() {
+        transform.translation.x = dragon.x as f32 * 180.;
+        transform.translation.y = dragon.y as f32 * 180.;
+        *visibility = Visibility::Visible;
+    }
+}
@@ -370,3 +370,63 @@ impl From<SquashedTriangle> for Mesh {
         return mesh;
     }
 }
+
+/// The part of a square between two diagonals cutting off its corner, on the `XY` plane centered at that corner.
+#[derive(Debug, Copy, Clone)]
+pub struct CornerBand {
+    /// Length of the square.
+    pub side_length: f32,
+    /// Distance along the sides from the corner to the near diagonal. Less than side_length.
+    pub inner: f32,
+    /// Distance along the sides from the corner to the far diagonal. More than side_length.
+    pub outer: f32,
+}
+
+impl Default for CornerBand {
+    fn default() -> Self {
+        CornerBand::new(1.0, 0.5, 1.5)
+    }
+}
+
+impl CornerBand {
+    pub fn new(side_length: f32, inner: f32, outer: f32) -> Self {
+        Self {
+            side_length,
+            inner,
+            outer,
+        }
+    }
+}
+
+impl From<CornerBand> for Mesh {
+    fn from(corner_band: CornerBand) -> Self {
+        let vertices = vec![
+            [corner_band.inner, 0.0, 0.0],
+            [corner_band.side_length, 0.0, 0.0],
+            [
+                corner_band.side_length,
+                corner_band.outer - corner_band.side_length,
+                0.0,
+            ],
+            [
+                corner_band.outer - corner_band.side_length,
+                corner_band.side_length,
+                0.0,
+            ],
+            [0.0, corner_band.side_length, 0.0],
+            [0.0, corner_band.inner, 0.0],
+        ];
+
+        let indices = Indices::U32(vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 5]);
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_indices(Some(indices));
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            vec![[0.0, 0.0, 1.0]; vertices.len()],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; vertices.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+        return mesh;
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use rustcassonne_core::game_board::GameplayData;
+use rustcassonne_core::players::Players;
+use rustcassonne_core::tiles::GameTileData;
+
+// The rules engine knows nothing about Bevy, so its state is wrapped here to live in the
+// world. Deref lets systems use them as the wrapped type.
+
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct TileDataRes(pub GameTileData);
+
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct GameplayDataRes(pub GameplayData);
+
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct PlayersRes(pub Players);
@@ -0,0 +1,136 @@
+use bevy::prelude::Resource;
+
+use rustcassonne_core::players::*;
+use rustcassonne_core::tiles::*;
+
+// Options picked when the game starts, from the command line.
+#[derive(Resource, Clone, Debug)]
+pub struct GameSettings {
+    pub player_names: Vec<String>,
+    // Same order as the names. Players without one get the first color nobody picked.
+    pub player_colors: Vec<Option<TeamColor>>,
+    // Picked at random when not given.
+    pub seed: Option<u64>,
+    pub expansions: Vec<Expansion>,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            player_names: vec!["Player 1".to_string(), "Player 2".to_string()],
+            player_colors: vec![None, None],
+            seed: None,
+            expansions: vec![],
+        }
+    }
+}
+
+pub const USAGE: &str =
+    "usage: rustcassonne [--player <name>[:<color>]]... [--seed <number>] [--expansion <name>]...";
+
+// Name of an expansion on the command line.
+pub fn parse_expansion(name: &str) -> Option<Expansion> {
+    match name {
+        "river" => Some(Expansion::River),
+        "inns" => Some(Expansion::InnsAndCathedrals),
+        "traders" => Some(Expansion::TradersAndBuilders),
+        "princess" => Some(Expansion::PrincessAndDragon),
+        "abbey" => Some(Expansion::AbbeyAndMayor),
+        "tower" => Some(Expansion::Tower),
+        "hills" => Some(Expansion::HillsAndSheep),
+        "bridges" => Some(Expansion::BridgesCastlesBazaars),
+        "gardens" => Some(Expansion::GardensAndAbbot),
+        _ => None,
+    }
+}
+
+// Name of a meeple color on the command line.
+pub fn parse_team_color(name: &str) -> Option<TeamColor> {
+    match name {
+        "blue" => Some(TeamColor::Blue),
+        "red" => Some(TeamColor::Red),
+        "green" => Some(TeamColor::Green),
+        "yellow" => Some(TeamColor::Yellow),
+        "black" => Some(TeamColor::Black),
+        "gray" => Some(TeamColor::Gray),
+        "pink" => Some(TeamColor::Pink),
+        _ => None,
+    }
+}
+
+impl GameSettings {
+    // Each `--player <name>` adds a player, in turn order. Two default players otherwise.
+    // `--player <name>:<color>` also picks the player's color.
+    // `--seed <number>` replays the tile draws of an earlier game. Each `--expansion <name>`
+    // adds an expansion's tiles and rules.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<GameSettings, String> {
+        let mut settings: GameSettings = Default::default();
+        let mut player_names: Vec<String> = vec![];
+        let mut player_colors: Vec<Option<TeamColor>> = vec![];
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--player" => match args.next() {
+                    Some(player) => match player.rsplit_once(':') {
+                        Some((name, color)) => match parse_team_color(color) {
+                            Some(color) => {
+                                player_names.push(name.to_string());
+                                player_colors.push(Some(color));
+                            }
+                            None => return Err(format!("unknown color {:?}", color)),
+                        },
+                        None => {
+                            player_names.push(player);
+                            player_colors.push(None);
+                        }
+                    },
+                    None => return Err("--player needs a name".to_string()),
+                },
+                "--seed" => match args.next().map(|seed| seed.parse::<u64>()) {
+                    Some(Ok(seed)) => settings.seed = Some(seed),
+                    _ => return Err("--seed needs a number".to_string()),
+                },
+                "--expansion" => match args.next().as_deref().map(parse_expansion) {
+                    Some(Some(expansion)) => {
+                        if !settings.expansions.contains(&expansion) {
+                            settings.expansions.push(expansion);
+                        }
+                    }
+                    _ => return Err(
+                        "--expansion needs one of: river, inns, traders, princess, abbey, tower, hills, bridges, gardens"
+                            .to_string(),
+                    ),
+                },
+                _ => return Err(format!("unknown argument {:?}", arg)),
+            }
+        }
+
+        if !player_names.is_empty() {
+            settings.player_names = player_names;
+            settings.player_colors = player_colors;
+        }
+        let max_players = max_players(&settings.expansions);
+        if !(MIN_PLAYERS..=max_players).contains(&settings.player_names.len()) {
+            return Err(format!(
+                "need {}-{} players, got {}",
+                MIN_PLAYERS,
+                max_players,
+                settings.player_names.len()
+            ));
+        }
+        // Checked once all expansions are known, since they bring their own colors.
+        let available_colors = team_colors(&settings.expansions);
+        for (color_idx, color) in settings.player_colors.iter().enumerate() {
+            let Some(color) = color else {
+                continue;
+            };
+            if !available_colors.contains(color) {
+                return Err(format!("{:?} needs an expansion that adds it", color));
+            }
+            if settings.player_colors[..color_idx].contains(&Some(*color)) {
+                return Err(format!("{:?} was picked twice", color));
+            }
+        }
+        return Ok(settings);
+    }
+}
@@ -1,8 +1,10 @@
 use std::f32::consts::PI;
 use std::vec;
 
+use rustcassonne_core::tiles::*;
+
+use crate::meeples_render::*;
 use crate::myshapes::*;
-use crate::tiles::*;
 
 use bevy::prelude::shape::Circle;
 use bevy::{prelude::*, render::mesh::Mesh, sprite::MaterialMesh2dBundle};
@@ -21,23 +23,9 @@ pub struct AreaEntityInfo {
     pub area_offset: Vec2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum TileDirection {
-    UP,
-    RIGHT,
-    DOWN,
-    LEFT,
-    NONE,
-}
-
-pub fn rotate_direction(dir: TileDirection) -> TileDirection {
-    match dir {
-        TileDirection::UP => TileDirection::RIGHT,
-        TileDirection::RIGHT => TileDirection::DOWN,
-        TileDirection::DOWN => TileDirection::LEFT,
-        TileDirection::LEFT => TileDirection::UP,
-        TileDirection::NONE => TileDirection::NONE,
-    }
+// Rotation of the tile entity for a direction, so what's drawn matches get_rotated_edge_areas.
+pub fn direction_to_angle(dir: TileDirection) -> f32 {
+    return direction_to_rotation_steps(dir) as f32 * PI / 2.0;
 }
 
 pub fn direction_to_offset(dir: TileDirection) -> Vec2 {
@@ -57,6 +45,10 @@ pub struct TileEntityInfo {
     pub dir: TileDirection,
 }
 
+// Where a free tile returns to when it is dropped somewhere it doesn't fit.
+#[derive(Component, Copy, Clone)]
+pub struct SnapBackPosition(pub Vec2);
+
 // Drag event that scales with zoom.
 // TODO: Scale with movement.
 #[derive(Event)]
@@ -144,6 +136,14 @@ pub fn get_area_type_info(area_type: AreaType) -> AreaTypeRenderInfo {
             color: Color::CRIMSON,
             z_height: -0.2,
         },
+        AreaType::Garden => AreaTypeRenderInfo {
+            color: Color::LIME_GREEN,
+            z_height: -0.2,
+        },
+        AreaType::CathedralTown => AreaTypeRenderInfo {
+            color: Color::PURPLE,
+            z_height: -0.3,
+        },
         AreaType::PennantTown => AreaTypeRenderInfo {
             color: Color::GOLD,
             z_height: -0.3,
@@ -164,17 +164,63 @@ pub fn get_area_type_info(area_type: AreaType) -> AreaTypeRenderInfo {
             color: Color::WHITE,
             z_height: -0.7,
         },
-        AreaType::Farm => AreaTypeRenderInfo {
-            color: Color::SEA_GREEN,
-            z_height: -0.8,
+        AreaType::InnRoad => AreaTypeRenderInfo {
+            color: Color::BISQUE,
+            z_height: -0.7,
         },
+        // Between farms and roads, so bridges go over the river.
         AreaType::Water => AreaTypeRenderInfo {
             color: Color::BLUE,
-            z_height: -0.9,
+            z_height: -0.75,
+        },
+        AreaType::AbbeyWall => AreaTypeRenderInfo {
+            color: Color::BEIGE,
+            z_height: -0.8,
+        },
+        AreaType::Farm => AreaTypeRenderInfo {
+            color: Color::SEA_GREEN,
+            z_height: -0.8,
         },
     }
 }
 
+// Towns with goods take the color of their goods.
+pub fn goods_to_color(goods: Goods) -> Color {
+    match goods {
+        Goods::Wine => Color::MAROON,
+        Goods::Grain => Color::rgb(0.8, 0.7, 0.3),
+        Goods::Cloth => Color::rgb(0.3, 0.45, 0.7),
+    }
+}
+
+pub fn tile_symbol_color(tile_type: TileType) -> Option<Color> {
+    if tile_type_has_volcano(tile_type) {
+        return Some(Color::ORANGE_RED);
+    }
+    if tile_type_has_dragon(tile_type) {
+        return Some(Color::DARK_GREEN);
+    }
+    if tile_type_has_portal(tile_type) {
+        return Some(Color::VIOLET);
+    }
+    if tile_type_has_princess(tile_type) {
+        return Some(Color::PINK);
+    }
+    if tile_type_has_tower_foundation(tile_type) {
+        return Some(Color::DARK_GRAY);
+    }
+    if tile_type_has_hill(tile_type) {
+        return Some(Color::OLIVE);
+    }
+    if tile_type_has_vineyard(tile_type) {
+        return Some(Color::INDIGO);
+    }
+    if tile_type_has_bazaar(tile_type) {
+        return Some(Color::TURQUOISE);
+    }
+    return None;
+}
+
 pub struct AreaRenderDatas {
     pub mesh: Mesh,
     pub offset: Vec2,
@@ -189,7 +235,7 @@ pub fn create_areas(
     tile_idx: TileIndex,
     mouse_world_pos: Vec2,
     area_render_datas: &Vec<AreaRenderDatas>, /* mesh, area_offset */
-) {
+) -> Entity {
     let mut relative_area_idx = 0;
 
     // check we have provided enough areas for this tile
@@ -222,6 +268,7 @@ pub fn create_areas(
                 area_idxs: tile_data.all_tiles[tile_idx].areas.clone(),
                 dir: TileDirection::UP,
             },
+            SnapBackPosition(mouse_world_pos),
             Highlight {
                 // TODO:  put material in resource
                 hovered: Some(HighlightKind::Fixed(
@@ -260,8 +307,11 @@ pub fn create_areas(
             area_offset: *area_offset,
         };
 
-        let area_type_info: AreaTypeRenderInfo =
+        let mut area_type_info: AreaTypeRenderInfo =
             get_area_type_info(tile_data.all_areas[absolute_area_idx].area_type);
+        if let Some(goods) = tile_data.all_areas[absolute_area_idx].goods {
+            area_type_info.color = goods_to_color(goods);
+        }
         let mut transform = Transform::from_translation(Vec3::new(
             area_offset.x,
             area_offset.y,
@@ -277,12 +327,30 @@ pub fn create_areas(
                     ..default()
                 },
                 area_info,
+                // Areas only take meeples once the tile is on the board.
+                Pickable::IGNORE,
+                RaycastPickTarget::default(),
+                On::<Pointer<Drop>>::send_event::<MeepleDropEvent>(),
             ))
             .id();
 
         commands.entity(parent).push_children(&[child]);
     }
 
+    // Volcanoes, dragons, portals, princesses, tower foundations, hills, vineyards and bazaars
+    // are marked in the middle of the tile.
+    if let Some(color) = tile_symbol_color(tile_data.all_tiles[tile_idx].tile_type) {
+        let symbol = commands
+            .spawn((MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(15.).into()).into(),
+                material: materials.add(color.into()),
+                transform: Transform::from_translation(Vec3::new(0., 0., 18.0)),
+                ..default()
+            },))
+            .id();
+        commands.entity(parent).push_children(&[symbol]);
+    }
+
     // Draw dot at reference location
     for c in vec![Color::BLACK, Color::WHITE] {
         let radius = if c == Color::WHITE { 6.0 } else { 9.0 };
@@ -305,6 +373,7 @@ pub fn create_areas(
             .id();
         commands.entity(parent).push_children(&[reference_dot]);
     }
+    return parent;
 }
 
 pub fn create_FFFF_C() -> Vec<AreaRenderDatas> {
@@ -323,6 +392,16 @@ pub fn create_FFFF_C() -> Vec<AreaRenderDatas> {
     return area_datas;
 }
 
+// A garden sits in one of the tile's fields, drawn like a small cloister.
+pub fn add_garden(mut area_datas: Vec<AreaRenderDatas>, offset: Vec2) -> Vec<AreaRenderDatas> {
+    area_datas.push(AreaRenderDatas {
+        mesh: shape::Quad::new(Vec2::new(40., 40.)).into(),
+        offset,
+        rotation: 0.0,
+    });
+    return area_datas;
+}
+
 pub fn create_FRFF_C() -> Vec<AreaRenderDatas> {
     let area_datas: Vec<AreaRenderDatas> = vec![
         AreaRenderDatas {
@@ -795,6 +874,199 @@ pub fn create_PPPP_0123() -> Vec<AreaRenderDatas> {
     return area_datas;
 }
 
+pub fn create_FWFF() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 180.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(30., 90.)).into(),
+            offset: Vec2::new(0., -45.),
+            rotation: 0.0,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_WFWF_02() -> Vec<AreaRenderDatas> {
+    return create_RFRF_02();
+}
+
+pub fn create_FWWF_12() -> Vec<AreaRenderDatas> {
+    return create_FRRF_12();
+}
+
+pub fn create_WRWF_02_C() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 75.)).into(),
+            offset: Vec2::new(0., 52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 30.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 75.)).into(),
+            offset: Vec2::new(0., -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(30., 27.)).into(),
+            offset: Vec2::new(0., -76.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(36., 36.)).into(),
+            offset: Vec2::new(0., -45.),
+            rotation: 0.0,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_WRWR_02_13() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(52.5, 52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 30.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(52.5, -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(30., 180.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(-52.5, -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(-52.5, 52.5),
+            rotation: 0.0,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_RWWR_03_12() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: RightTriangle::new(75.0).into(),
+            offset: Vec2::new(90., 90.),
+            rotation: PI,
+        },
+        AreaRenderDatas {
+            mesh: Trapezoid::new(105.0, 30.0).into(),
+            offset: Vec2::new(90., 90.),
+            rotation: PI,
+        },
+        AreaRenderDatas {
+            mesh: CornerBand::new(180.0, 105.0, 255.0).into(),
+            offset: Vec2::new(-90., -90.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: Trapezoid::new(105.0, 30.0).into(),
+            offset: Vec2::new(-90., -90.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: RightTriangle::new(75.0).into(),
+            offset: Vec2::new(-90., -90.),
+            rotation: 0.0,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_WRWT_02() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 75.)).into(),
+            offset: Vec2::new(0., 52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 30.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(52.5, -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(30., 75.)).into(),
+            offset: Vec2::new(0., -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(75., 75.)).into(),
+            offset: Vec2::new(-52.5, -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: SquashedTriangle::new(180.0).into(),
+            offset: Vec2::new(0., 90.0),
+            rotation: PI,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_WTWT_02() -> Vec<AreaRenderDatas> {
+    let area_datas: Vec<AreaRenderDatas> = vec![
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 75.)).into(),
+            offset: Vec2::new(0., 52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 30.)).into(),
+            offset: Vec2::new(0., 0.),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: shape::Quad::new(Vec2::new(180., 75.)).into(),
+            offset: Vec2::new(0., -52.5),
+            rotation: 0.0,
+        },
+        AreaRenderDatas {
+            mesh: SquashedTriangle::new(180.0).into(),
+            offset: Vec2::new(0., -90.0),
+            rotation: 0.,
+        },
+        AreaRenderDatas {
+            mesh: SquashedTriangle::new(180.0).into(),
+            offset: Vec2::new(0., 90.0),
+            rotation: PI,
+        },
+    ];
+    return area_datas;
+}
+
+pub fn create_TWWT_03() -> Vec<AreaRenderDatas> {
+    return create_TRRT_03_12();
+}
+
 pub fn create_tile(
     tile_idx: TileIndex,
     window: &Window,
@@ -805,9 +1077,58 @@ pub fn create_tile(
     camera: &Camera,
     camera_transform: &GlobalTransform,
 ) {
-    // let mut tile: &Tile = &tile_data.all_tiles[tile_id];
-
     let mouse_world_pos: Vec2 = mouse_to_world_position(window, camera, camera_transform);
+    create_tile_at(
+        tile_idx,
+        mouse_world_pos,
+        tile_data,
+        commands,
+        meshes,
+        materials,
+    );
+}
+
+// Bridges & Castles. A bridge is a road of its own laid over the middle of a placed tile.
+pub fn create_bridge(
+    tile_idx: TileIndex,
+    area_idx: TileAreaIndex,
+    axis: BridgeAxis,
+    position: Vec2,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    let size = match axis {
+        BridgeAxis::NorthSouth => Vec2::new(20., 180.),
+        BridgeAxis::EastWest => Vec2::new(180., 20.),
+    };
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Quad::new(size).into()).into(),
+            material: materials.add(ColorMaterial::from(Color::ANTIQUE_WHITE)),
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..default()
+        },
+        AreaEntityInfo {
+            tile_idx,
+            area_idx,
+            area_offset: Vec2::ZERO,
+        },
+        Pickable::default(),
+        RaycastPickTarget::default(),
+        On::<Pointer<Drop>>::send_event::<MeepleDropEvent>(),
+    ));
+}
+
+pub fn create_tile_at(
+    tile_idx: TileIndex,
+    pos: Vec2,
+    tile_data: &GameTileData,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) -> Entity {
+    // let mut tile: &Tile = &tile_data.all_tiles[tile_id];
 
     let tile_type: TileType = tile_data.all_tiles[tile_idx].tile_type;
 
@@ -839,27 +1160,81 @@ pub fn create_tile(
         TileType::TRTT_013 => area_data = create_TRTT_013(),
         TileType::PRPP_013 => area_data = create_PRPP_013(),
         TileType::PPPP_0123 => area_data = create_PPPP_0123(),
-        TileType::FWFF => todo!(),
-        TileType::WFWF_02 => todo!(),
-        TileType::FWWF_12 => todo!(),
-        TileType::WRWF_02_C => todo!(),
-        TileType::WRWR_02_13 => todo!(),
-        TileType::RWWR_03_12 => todo!(),
-        TileType::WRWT_02 => todo!(),
-        TileType::WTWT_02 => todo!(),
-        TileType::TWWT_03 => todo!(),
+        TileType::FWFF => area_data = create_FWFF(),
+        TileType::WFWF_02 => area_data = create_WFWF_02(),
+        TileType::FWWF_12 => area_data = create_FWWF_12(),
+        TileType::WRWF_02_C => area_data = create_WRWF_02_C(),
+        TileType::WRWR_02_13 => area_data = create_WRWR_02_13(),
+        TileType::RWWR_03_12 => area_data = create_RWWR_03_12(),
+        TileType::WRWT_02 => area_data = create_WRWT_02(),
+        TileType::WTWT_02 => area_data = create_WTWT_02(),
+        TileType::TWWT_03 => area_data = create_TWWT_03(),
+        TileType::RFRF_02_Inn => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Inn => area_data = create_FRRF_12(),
+        TileType::RFRT_02_Inn => area_data = create_RFRT_02(),
+        TileType::RRFT_01_Inn => area_data = create_RRFT_01(),
+        TileType::TRRT_03_12_Inn => area_data = create_TRRT_03_12(),
+        TileType::PRRP_03_12_Inn => area_data = create_PRRP_03_12(),
+        TileType::TTTT_0123_Cathedral => area_data = create_PPPP_0123(),
+        TileType::FFFT_Wine => area_data = create_FFFT(),
+        TileType::TFFT_03_Wine => area_data = create_TFFT_03(),
+        TileType::TRRT_03_12_Wine => area_data = create_TRRT_03_12(),
+        TileType::FFFT_Grain => area_data = create_FFFT(),
+        TileType::TFTF_02_Grain => area_data = create_TFTF_02(),
+        TileType::TFTT_013_Grain => area_data = create_TFTT_013(),
+        TileType::FTFT_Cloth => area_data = create_FTFT(),
+        TileType::TFFT_03_Cloth => area_data = create_TFFT_03(),
+        TileType::TRTT_013_Cloth => area_data = create_TRTT_013(),
+        TileType::FFFF_Volcano => area_data = create_PPPP_0123(),
+        TileType::FFFT_Volcano => area_data = create_FFFT(),
+        TileType::RFRF_02_Dragon => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Dragon => area_data = create_FRRF_12(),
+        TileType::TFFT_03_Dragon => area_data = create_TFFT_03(),
+        TileType::FTFT_Dragon => area_data = create_FTFT(),
+        TileType::RFRF_02_Portal => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Portal => area_data = create_FRRF_12(),
+        TileType::FFFT_Portal => area_data = create_FFFT(),
+        TileType::FFFT_Princess => area_data = create_FFFT(),
+        TileType::TFFT_03_Princess => area_data = create_TFFT_03(),
+        TileType::TFTT_013_Princess => area_data = create_TFTT_013(),
+        TileType::Abbey => area_data = create_FFFF_C(),
+        TileType::RFRF_02_Tower => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Tower => area_data = create_FRRF_12(),
+        TileType::RRRF_Tower => area_data = create_RRRF(),
+        TileType::FFFT_Tower => area_data = create_FFFT(),
+        TileType::TFFT_03_Tower => area_data = create_TFFT_03(),
+        TileType::TFTF_02_Tower => area_data = create_TFTF_02(),
+        TileType::RFRF_02_Hill => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Hill => area_data = create_FRRF_12(),
+        TileType::FFFT_Hill => area_data = create_FFFT(),
+        TileType::TFFT_03_Hill => area_data = create_TFFT_03(),
+        TileType::FFFF_Vineyard => area_data = create_PPPP_0123(),
+        TileType::RFRF_02_Vineyard => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Vineyard => area_data = create_FRRF_12(),
+        TileType::RFRF_02_Bazaar => area_data = create_RFRF_02(),
+        TileType::FRRF_12_Bazaar => area_data = create_FRRF_12(),
+        TileType::TFFT_03_Bazaar => area_data = create_TFFT_03(),
+        TileType::FFFF_Garden => area_data = create_FFFF_C(),
+        TileType::RFRF_02_Garden => area_data = add_garden(create_RFRF_02(), Vec2::new(0., -52.5)),
+        TileType::FFFT_Garden => area_data = add_garden(create_FFFT(), Vec2::new(0., -30.)),
     }
 
-    create_areas(
-        commands,
-        meshes,
-        materials,
-        tile_data,
-        tile_idx,
-        mouse_world_pos,
-        &area_data,
+    return create_areas(
+        commands, meshes, materials, tile_data, tile_idx, pos, &area_data,
     );
 }
+
+// Tiles on the board can't be dragged or picked up again.
+pub fn lock_tile_in_place(tile_entity: Entity, commands: &mut Commands) {
+    // TODO: still crashes when the tile is dragged over placeholder
+    commands
+        .entity(tile_entity)
+        .remove::<On<Pointer<Drag>>>()
+        .remove::<On<Pointer<Drop>>>()
+        .remove::<On<Pointer<DragStart>>>()
+        .remove::<On<Pointer<DragEnd>>>()
+        .insert(Pickable::IGNORE);
+}
 pub fn create_placeholder_tile(
     placeholder_tile_idx: TileIndex,
     commands: &mut Commands,